@@ -0,0 +1,54 @@
+//! Compares A* against the precomputed distance table on the built-in maze and big generated mazes.
+//! Run with `cargo bench --bench pathfinding`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+use rucman::a_star::a_star;
+use rucman::character::Character;
+use rucman::distance::DistanceTable;
+use rucman::generator::{generate, MazeConfig};
+use rucman::grid::grid::{Grid, Mover};
+use rucman::point::Vector2;
+
+/// The mazes to compare on, along with a far apart start and end in each.
+fn mazes() -> Vec<(String, Grid, Vector2, Vector2)> {
+    let mut res = Vec::new();
+    let builtin = Grid::new();
+    res.push((String::from("builtin"), builtin, Vector2(1, 1), Vector2(25, 26)));
+
+    for size in [63, 127] {
+        let grid = generate(&MazeConfig { width: size, height: size, tunnels: 2 }, 7).unwrap();
+        let start = grid.get_spawn(Character::Rucman);
+        let end = grid.get_scatter_position(Character::Inky);
+        res.push((format!("generated {}x{}", grid.get_width(), grid.get_height()), grid, start, end));
+    }
+
+    res
+}
+
+fn pathfinding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pathfinding");
+    for (name, grid, start, end) in mazes() {
+        group.bench_with_input(BenchmarkId::new("a_star", &name), &grid, |b, grid| {
+            b.iter(|| a_star(grid, black_box(start), black_box(end), Mover::Ghost, true))
+        });
+
+        let mut table = DistanceTable::new(&grid, Mover::Ghost);
+        table.distance(start, end); // Make sure the field is built before timing lookups.
+        group.bench_function(BenchmarkId::new("table path", &name), |b| {
+            b.iter(|| table.path(black_box(start), black_box(end)))
+        });
+        group.bench_function(BenchmarkId::new("table next step", &name), |b| {
+            b.iter(|| table.next_step(black_box(start), black_box(end)))
+        });
+
+        group.bench_with_input(BenchmarkId::new("table build", &name), &grid, |b, grid| {
+            b.iter(|| DistanceTable::new(grid, Mover::Ghost))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pathfinding);
+criterion_main!(benches);
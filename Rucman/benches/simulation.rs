@@ -0,0 +1,89 @@
+//! Times the pieces of the simulation that run every frame, along with a whole headless game.
+//! Run with `cargo bench --bench simulation`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+use rucman::camera::Camera;
+use rucman::direction::Direction;
+use rucman::game::{Game, StepOutcome};
+use rucman::grid::grid::{Grid, GridPoint};
+use rucman::level::LevelPack;
+use rucman::point::Vector2;
+use rucman::render::{draw_frame, render_frame};
+
+/// How many frames the headless game runs for.
+const HEADLESS_TICKS: u32 = 10_000;
+
+/// Plays the classic pack without drawing anything, turning Rucman every few frames so he covers the maze.
+/// Respawns when Rucman is caught and starts over when the game ends, so it always runs for the provided number of frames.
+fn run_headless(ticks: u32) -> Game {
+    let directions = Direction::directions();
+    let mut game = Game::new(LevelPack::classic()).unwrap();
+    for tick in 0..ticks {
+        if tick % 8 == 0 { game.turn(directions[(tick / 8) as usize % directions.len()]); }
+
+        match game.step() {
+            StepOutcome::Caught(_) => game.respawn(),
+            StepOutcome::LevelComplete => { game.next_level().unwrap(); },
+            StepOutcome::Continue => {},
+        }
+
+        if game.is_over() { game = Game::new(LevelPack::classic()).unwrap(); }
+    }
+
+    game
+}
+
+/// Finds every pellet and power pellet in a maze.
+fn pellet_positions(grid: &Grid) -> Vec<Vector2> {
+    let mut res = Vec::new();
+    for (row, points) in grid.get_maze().iter().enumerate() {
+        for (col, point) in points.iter().enumerate() {
+            if matches!(point, GridPoint::Pellet | GridPoint::PowerPellet) {
+                res.push(Vector2(col as i32, row as i32));
+            }
+        }
+    }
+
+    res
+}
+
+fn simulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulation");
+
+    // Steps a game already in progress, so the distance tables are built and the timing is of a typical frame.
+    let mut game = run_headless(100);
+    group.bench_function("step", |b| {
+        b.iter(|| {
+            if let StepOutcome::Caught(_) = game.step() { game.respawn(); }
+        })
+    });
+
+    let pellets = pellet_positions(&Grid::new());
+    group.bench_function("eat every pellet", |b| {
+        b.iter_batched_ref(Grid::new, |grid| {
+            for pos in &pellets {
+                black_box(grid.eat(pos)).unwrap();
+            }
+        }, BatchSize::SmallInput)
+    });
+
+    let mut camera = Camera::default();
+    group.bench_function("render frame", |b| b.iter(|| render_frame(black_box(&game), (80, 40), "", &mut camera, false)));
+
+    let mut buffer = Vec::new();
+    group.bench_function("draw frame", |b| {
+        b.iter(|| {
+            buffer.clear();
+            draw_frame(&mut buffer, black_box(&game), (80, 40), "", &mut camera, false).unwrap();
+        })
+    });
+
+    group.sample_size(10);
+    group.bench_function("headless game", |b| b.iter(|| run_headless(black_box(HEADLESS_TICKS))));
+    group.finish();
+}
+
+criterion_group!(benches, simulation);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
+use tracing::debug;
+
 use crate::direction::Direction;
-use crate::grid::grid::Grid;
+use crate::grid::grid::{Grid, Mover};
 use crate::point::Vector2;
 
 use std::cmp::Ordering;
@@ -24,9 +26,9 @@ impl PartialOrd for State {
     }
 }
 
-/// Finds the shortest path from the start point to the end point.
+/// Finds the shortest path from the start point to the end point that the provided mover is allowed to take.
 /// Returns a path represented by a Vec of Vector2's if a path is found.
-pub fn a_star(grid: &Grid, start: Vector2, end: Vector2, already_on_start: bool) -> Option<Vec<Vector2>> {
+pub fn a_star(grid: &Grid, start: Vector2, end: Vector2, mover: Mover, already_on_start: bool) -> Option<Vec<Vector2>> {
     let mut open_set = BinaryHeap::new(); // Sorts frontier by the minimum f-scores.
     open_set.push(State{position: start, f_score: Vector2::side_distance(start, end)});
 
@@ -34,25 +36,14 @@ pub fn a_star(grid: &Grid, start: Vector2, end: Vector2, already_on_start: bool)
     let mut g_score = HashMap::new(); // Shortest known distances for all nodes.
     g_score.insert(start, 0);
 
-    while !open_set.is_empty() {
-        let current = open_set.pop(); // Get element with lowest f-score.
-        if current.is_none() { break; } //Shouldn't happen.
-        let current = current.unwrap();
+    while let Some(current) = open_set.pop() { // Get element with lowest f-score.
 
         if current.position == end { return Some(reconstruct_path(came_from, current.position, already_on_start)); } // If current is the end, return the path. 
 
         for direction in Direction::directions() { // Calculate f-scores for all valid neighbors.
-            let direction = {
-                match direction {
-                    Direction::Up(dir) |
-                    Direction::Down(dir) |
-                    Direction::Left(dir) |
-                    Direction::Right(dir) => dir
-                }
-            };
-
-            let next = current.position + direction;
-            if !grid.is_valid_pos(&next) { continue; }
+            // Stepping onto a teleporter puts you at its destination. The heuristic ignores teleporters,
+            // so paths through them are found but aren't always the shortest.
+            let Some((next, _)) = grid.try_move(&current.position, direction, mover) else { continue; };
 
             let tentative_g_score = g_score.get(&current.position).unwrap_or(&i32::MAX) + 1;
             //If this path to this neighbor is shorter than previously recorded, put it back into the open set.
@@ -69,6 +60,7 @@ pub fn a_star(grid: &Grid, start: Vector2, end: Vector2, already_on_start: bool)
         }
     }
 
+    debug!(?start, ?end, ?mover, "A* found no path");
     None //No path was found.
 }
 
@@ -77,12 +69,48 @@ pub fn a_star(grid: &Grid, start: Vector2, end: Vector2, already_on_start: bool)
 fn reconstruct_path(came_from: HashMap<Vector2, Vector2>, current: Vector2, already_on_start: bool) -> Vec<Vector2> {
     let mut current = current;
     let mut path = vec![current];
-    while came_from.contains_key(&current) {
-        current = *came_from.get(&current).unwrap(); //It shouldn't be none since contains_key is checked beforehand.
+    while let Some(previous) = came_from.get(&current) {
+        current = *previous;
         path.push(current);
     }
 
     if already_on_start { path.pop(); } // Remove the start from the path if we're already there.
 
     path
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if paths only go through tiles the mover is allowed on.
+    #[test]
+    fn path_respects_mover() {
+        let grid = Grid::from(vec![
+            "#######".chars().collect(),
+            "#..-..#".chars().collect(),
+            "#.###.#".chars().collect(),
+            "#.....#".chars().collect(),
+            "#######".chars().collect(),
+        ]);
+        let start = Vector2(1, 1);
+        let end = Vector2(5, 1);
+        assert_eq!(a_star(&grid, start, end, Mover::Ghost, true).unwrap().len(), 4);
+        assert_eq!(a_star(&grid, start, end, Mover::EatenGhost, true).unwrap().len(), 4);
+        let path = a_star(&grid, start, end, Mover::Rucman, true).unwrap();
+        assert_eq!(path.len(), 8);
+        assert!(!path.contains(&Vector2(3, 1)));
+    }
+
+    /// Tests if no path is found when the mover can't get there.
+    #[test]
+    fn no_path() {
+        let grid = Grid::from(vec![
+            "#####".chars().collect(),
+            "#.<.#".chars().collect(),
+            "#####".chars().collect(),
+        ]);
+        assert_eq!(a_star(&grid, Vector2(1, 1), Vector2(3, 1), Mover::Rucman, true), None);
+        assert_eq!(a_star(&grid, Vector2(3, 1), Vector2(1, 1), Mover::Rucman, true), Some(vec![Vector2(1, 1), Vector2(2, 1)]));
+    }
+}
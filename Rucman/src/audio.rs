@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use crate::events::{GameEvent, Subscriber};
+
+/// Plays sounds for game events on the terminal bell, the one sound every terminal has. It rings when Rucman is
+/// caught, clears a level or earns a life.
+#[derive(Debug)]
+pub struct TerminalBell<W: Write + Send> {
+    out: W,
+}
+
+impl<W: Write + Send> TerminalBell<W> {
+    /// Creates a bell that rings on the provided output, usually the terminal.
+    pub fn new(out: W) -> Self {
+        TerminalBell { out }
+    }
+}
+
+impl<W: Write + Send> Subscriber for TerminalBell<W> {
+    fn notify(&mut self, event: &GameEvent) {
+        if !matches!(event, GameEvent::RucmanCaught { .. } | GameEvent::LevelCleared { .. } | GameEvent::ExtraLife { .. }) { return; }
+
+        // A bell that can't ring isn't worth stopping the game over.
+        let _ = self.out.write_all(b"\x07").and_then(|_| self.out.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+    use crate::point::Vector2;
+
+    /// Tests if the bell only rings for the events that matter.
+    #[test]
+    fn rings() {
+        let mut bell = TerminalBell::new(Vec::new());
+        bell.notify(&GameEvent::PelletEaten { position: Vector2(1, 1) });
+        bell.notify(&GameEvent::RucmanCaught { by: Character::Clyde });
+        bell.notify(&GameEvent::ExtraLife { lives: 4 });
+        assert_eq!(bell.out, b"\x07\x07");
+    }
+}
@@ -0,0 +1,203 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use std::ops::ControlFlow;
+
+use crate::character::Vulnerability;
+use crate::direction::Direction;
+use crate::distance::breadth_first;
+use crate::game::Game;
+use crate::grid::grid::{Grid, Mover};
+use crate::mcts::{MctsBot, MctsConfig};
+use crate::point::Vector2;
+
+/// Plays as Rucman. Looks at the game once per frame and decides which way he should turn, if at all.
+pub trait Bot: Send {
+    /// Chooses the direction to turn Rucman before the next step. None leaves him going the way he was.
+    fn choose_direction(&mut self, game: &Game) -> Option<Direction>;
+}
+
+/// Never turns, leaving Rucman to run into the first wall. A baseline for how long the ghosts take to catch someone
+/// standing still.
+#[derive(Debug, Default, Clone)]
+pub struct IdleBot;
+
+impl Bot for IdleBot {
+    fn choose_direction(&mut self, _game: &Game) -> Option<Direction> {
+        None
+    }
+}
+
+/// Wanders the maze, picking a random way to go at every junction and whenever it's blocked. Never turns back
+/// unless it's at a dead end.
+#[derive(Debug, Clone)]
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    /// Creates a bot whose choices come from the provided seed.
+    pub fn new(seed: u64) -> Self {
+        RandomBot { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Bot for RandomBot {
+    fn choose_direction(&mut self, game: &Game) -> Option<Direction> {
+        let rucman = game.get_rucman();
+        let (position, direction) = (rucman.get_position(), rucman.get_direction());
+        let open: Vec<Direction> = Direction::directions().into_iter()
+            .filter(|dir| game.get_grid().can_move(&position, *dir, Mover::Rucman))
+            .collect();
+
+        // Keep going down corridors.
+        let blocked = !open.contains(&direction);
+        if !blocked && open.len() <= 2 { return None; }
+
+        let forward: Vec<Direction> = open.iter().copied().filter(|dir| *dir != direction.opposite()).collect();
+        let choices = if forward.is_empty() { open } else { forward };
+        if choices.is_empty() { return None; }
+
+        Some(choices[self.rng.random_range(0..choices.len())])
+    }
+}
+
+/// Heads for the nearest pellet, or the nearest vulnerable ghost if one can be reached first, going the shortest
+/// way that stays out of reach of the invulnerable ghosts. When every way is blocked by ghosts, it runs from
+/// the closest one instead.
+#[derive(Debug, Clone)]
+pub struct GreedyBot {
+    danger_radius: u32, // Cells within this many ghost steps of an invulnerable ghost are avoided.
+}
+
+impl Default for GreedyBot {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl GreedyBot {
+    /// Creates a bot that keeps the provided number of steps away from invulnerable ghosts.
+    pub fn new(danger_radius: u32) -> Self {
+        GreedyBot { danger_radius }
+    }
+
+    /// Marks every cell an invulnerable ghost could reach within the danger radius.
+    fn danger_zone(&self, game: &Game) -> Vec<bool> {
+        let grid = game.get_grid();
+        let ghosts: Vec<Vector2> = game.get_ghosts().iter()
+            .filter(|ghost| ghost.get_vulnerability() == Vulnerability::Invulnerable)
+            .map(|ghost| ghost.get_position())
+            .collect();
+
+        let mut res = vec![false; grid.get_width() * grid.get_height()];
+        breadth_first::<()>(grid, &ghosts, Mover::Ghost, |pos, steps, _| {
+            if let Some(cell) = grid.cell_id(&pos) { res[cell.index()] = true; }
+            ControlFlow::Continue(steps < self.danger_radius)
+        });
+
+        res
+    }
+
+    /// Searches breadth first from the start for the closest target, never stepping into the danger zone.
+    /// Returns the first direction of the way there.
+    fn search(grid: &Grid, start: Vector2, danger: &[bool], is_target: impl Fn(Vector2) -> bool) -> Option<Direction> {
+        breadth_first(grid, &[start], Mover::Rucman, |pos, _, first| {
+            let Some(first) = first else { return ControlFlow::Continue(true); }; // Rucman starts wherever he is.
+            if grid.cell_id(&pos).is_some_and(|cell| danger[cell.index()]) { ControlFlow::Continue(false) }
+            else if is_target(pos) { ControlFlow::Break(first) }
+            else { ControlFlow::Continue(true) }
+        })
+    }
+
+    /// Picks the step that ends up furthest from the closest invulnerable ghost.
+    fn flee(game: &Game) -> Option<Direction> {
+        let grid = game.get_grid();
+        let position = game.get_rucman().get_position();
+        let ghosts: Vec<Vector2> = game.get_ghosts().iter()
+            .filter(|ghost| ghost.get_vulnerability() == Vulnerability::Invulnerable)
+            .map(|ghost| ghost.get_position())
+            .collect();
+
+        Direction::directions().into_iter()
+            .filter_map(|direction| grid.try_move(&position, direction, Mover::Rucman).map(|(next, _)| (direction, next)))
+            .max_by_key(|(_, next)| ghosts.iter().map(|ghost| Vector2::side_distance(*ghost, *next)).min().unwrap_or(0))
+            .map(|(direction, _)| direction)
+    }
+}
+
+impl Bot for GreedyBot {
+    fn choose_direction(&mut self, game: &Game) -> Option<Direction> {
+        let grid = game.get_grid();
+        let danger = self.danger_zone(game);
+        let prey: Vec<Vector2> = game.get_ghosts().iter()
+            .filter(|ghost| ghost.get_vulnerability() == Vulnerability::Vulnerable)
+            .map(|ghost| ghost.get_position())
+            .collect();
+
+        let start = game.get_rucman().get_position();
+        Self::search(grid, start, &danger, |pos| prey.contains(&pos) || grid.has_pellet(&pos))
+            .or_else(|| Self::flee(game))
+    }
+}
+
+/// The names `bot_by_name` knows about.
+pub const BOT_NAMES: [&str; 4] = ["idle", "random", "greedy", "mcts"];
+
+/// Creates the bot with the provided name, seeding it if it makes random choices.
+/// The search bot takes how many futures to search each frame after a colon, such as `mcts:50`.
+pub fn bot_by_name(name: &str, seed: u64) -> Option<Box<dyn Bot>> {
+    let (name, budget) = match name.split_once(':') {
+        Some((name, budget)) => (name, Some(budget.parse().ok()?)),
+        None => (name, None),
+    };
+
+    match (name, budget) {
+        ("idle", None) => Some(Box::new(IdleBot)),
+        ("random", None) => Some(Box::new(RandomBot::new(seed))),
+        ("greedy", None) => Some(Box::new(GreedyBot::default())),
+        ("mcts", budget) => {
+            let config = MctsConfig::default();
+            Some(Box::new(MctsBot::new(MctsConfig { budget: budget.unwrap_or(config.budget), ..config }, seed)))
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LevelPack;
+    use crate::simulate::simulate_game;
+
+    /// Tests if the random bot keeps Rucman moving instead of stuck against walls.
+    #[test]
+    fn random_bot_moves() {
+        let mut game = Game::with_seed(LevelPack::classic(), 1).unwrap();
+        let mut bot = RandomBot::new(1);
+        let mut visited = std::collections::HashSet::new();
+        for _ in 0..100 {
+            if let Some(direction) = bot.choose_direction(&game) { game.turn(direction); }
+            if game.step() != crate::game::StepOutcome::Continue { break; }
+            visited.insert(game.get_rucman().get_position());
+        }
+
+        assert!(visited.len() > 10);
+        assert!(bot_by_name("idle", 0).is_some() && bot_by_name("nobody", 0).is_none());
+        assert!(bot_by_name("mcts:50", 0).is_some() && bot_by_name("mcts:lots", 0).is_none() && bot_by_name("idle:5", 0).is_none());
+    }
+
+    /// Tests if the greedy bot eats far more pellets per life than wandering at random.
+    #[test]
+    fn greedy_bot_beats_random() {
+        let pellets_per_life = |name: &str| -> f64 {
+            (0..8).map(|seed| {
+                let mut bot = bot_by_name(name, seed).unwrap();
+                simulate_game(LevelPack::classic(), bot.as_mut(), seed, 3_000).unwrap().pellets_per_life
+            }).sum()
+        };
+
+        let (greedy, random) = (pellets_per_life("greedy"), pellets_per_life("random"));
+        assert!(greedy > 1.5 * random, "Greedy bot ate {greedy} pellets per life to the random bot's {random}");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::point::Vector2;
+
+/// Follows a target around a maze too big to show all at once.
+///
+/// The target can move around a dead zone in the middle of the view without the camera moving. Once it leaves,
+/// the camera scrolls one cell per frame to keep up. Jumps further than a single step, such as through a
+/// teleporter or when respawning, snap the camera to center on the target instead of scrolling across the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    origin: Vector2, // The maze position shown in the view's top left corner.
+    dead_zone: (i32, i32), // How far the target can get from the view's center on each axis before the camera scrolls.
+    last_target: Option<Vector2>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new((4, 3))
+    }
+}
+
+impl Camera {
+    /// Creates a camera with the provided dead zone, given as how far the target can get from the center of the
+    /// view horizontally and vertically before the camera starts scrolling.
+    pub fn new(dead_zone: (u16, u16)) -> Self {
+        Camera { origin: Vector2(0, 0), dead_zone: (dead_zone.0 as i32, dead_zone.1 as i32), last_target: None }
+    }
+
+    /// Retrieves the maze position shown in the view's top left corner.
+    pub fn get_origin(&self) -> Vector2 {
+        self.origin
+    }
+
+    /// Moves the camera for the target's new position. The view and maze sizes are in cells, width then height.
+    pub fn follow(&mut self, target: Vector2, view: (usize, usize), maze: (usize, usize)) {
+        let (view_width, view_height) = (view.0 as i32, view.1 as i32);
+        let jumped = self.last_target.is_none_or(|last| Vector2::side_distance(last, target) > 1);
+        self.last_target = Some(target);
+
+        if jumped {
+            self.origin = Vector2(target.0 - view_width / 2, target.1 - view_height / 2);
+        }
+        else {
+            self.origin.0 += Self::scroll(target.0 - (self.origin.0 + view_width / 2), self.dead_zone.0);
+            self.origin.1 += Self::scroll(target.1 - (self.origin.1 + view_height / 2), self.dead_zone.1);
+        }
+
+        // Never show past the edges of the maze, unless it's smaller than the view.
+        self.origin.0 = self.origin.0.min(maze.0 as i32 - view_width).max(0);
+        self.origin.1 = self.origin.1.min(maze.1 as i32 - view_height).max(0);
+    }
+
+    /// How far to scroll along an axis when the target is the provided offset from the view's center.
+    fn scroll(offset: i32, dead_zone: i32) -> i32 {
+        if offset > dead_zone { 1 }
+        else if offset < -dead_zone { -1 }
+        else { 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if the camera only scrolls once the target leaves the dead zone, and then one cell at a time.
+    #[test]
+    fn dead_zone() {
+        let mut camera = Camera::new((2, 2));
+        camera.follow(Vector2(50, 50), (20, 10), (100, 100));
+        assert_eq!(camera.get_origin(), Vector2(40, 45));
+
+        camera.follow(Vector2(51, 50), (20, 10), (100, 100));
+        camera.follow(Vector2(52, 50), (20, 10), (100, 100));
+        assert_eq!(camera.get_origin(), Vector2(40, 45));
+
+        camera.follow(Vector2(53, 50), (20, 10), (100, 100));
+        assert_eq!(camera.get_origin(), Vector2(41, 45));
+        camera.follow(Vector2(53, 49), (20, 10), (100, 100));
+        assert_eq!(camera.get_origin(), Vector2(41, 45));
+    }
+
+    /// Tests if jumps snap the camera and it stays inside the maze.
+    #[test]
+    fn snap_and_clamp() {
+        let mut camera = Camera::new((2, 2));
+        camera.follow(Vector2(50, 50), (20, 10), (100, 100));
+        camera.follow(Vector2(1, 50), (20, 10), (100, 100)); // Through a teleporter.
+        assert_eq!(camera.get_origin(), Vector2(0, 45));
+
+        camera.follow(Vector2(99, 99), (20, 10), (100, 100));
+        assert_eq!(camera.get_origin(), Vector2(80, 90));
+
+        camera.follow(Vector2(5, 5), (20, 10), (15, 8)); // The maze fits in the view.
+        assert_eq!(camera.get_origin(), Vector2(0, 0));
+    }
+}
@@ -1,13 +1,14 @@
+use tracing::warn;
+
 use std::vec;
 use std::fmt::Display;
 
-use crate::grid::grid::Grid;
-use crate::a_star;
+use crate::grid::grid::{Grid, Mover};
 use crate::point::Vector2;
-use crate::Direction;
+use crate::direction::Direction;
 
 /// Denotes which rucman character is currently represented.
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Character {
     Rucman,
     Blinky, 
@@ -21,6 +22,7 @@ pub enum Character {
 pub enum Vulnerability {
     Invulnerable,
     Vulnerable,
+    Eaten, // Heading back to its spawn after being eaten. Passes through Rucman until it's home.
 }
 
 /// Denotes if the ghost should chase rucman or scatter.
@@ -30,6 +32,26 @@ pub enum GhostMode {
     Chase,
 }
 
+/// Denotes Blinky's "Cruise Elroy" stage. Elroy Blinky moves faster and keeps chasing during scatter phases.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ElroyStage {
+    Inactive,
+    One,
+    Two,
+}
+
+impl ElroyStage {
+    /// Returns true if Elroy Blinky gets an extra step on the provided frame.
+    /// Stage one is about an eighth faster and stage two is about a quarter faster.
+    pub fn is_extra_step(&self, frames: u128) -> bool {
+        match self {
+            ElroyStage::Inactive => false,
+            ElroyStage::One => frames.is_multiple_of(8),
+            ElroyStage::Two => frames.is_multiple_of(4),
+        }
+    }
+}
+
 impl From<Character> for char {
     fn from(value: Character) -> Self {
         match value {
@@ -42,6 +64,39 @@ impl From<Character> for char {
     }
 }
 
+impl TryFrom<char> for Character {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'R' => Ok(Character::Rucman),
+            'I' => Ok(Character::Inky),
+            'B' => Ok(Character::Blinky),
+            'P' => Ok(Character::Pinky),
+            'C' => Ok(Character::Clyde),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Character {
+    /// Returns a Vec of every character to be iterated through.
+    pub fn characters() -> Vec<Self> {
+        vec![
+            Character::Rucman,
+            Character::Blinky,
+            Character::Pinky,
+            Character::Inky,
+            Character::Clyde,
+        ]
+    }
+
+    /// Finds the character with the provided name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::characters().into_iter().find(|character| character.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
 impl Display for Character {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match *self {
@@ -76,6 +131,7 @@ impl From<&CharacterData> for char {
                 match value.vulnerability {
                     Vulnerability::Invulnerable => char::from(value.character),
                     Vulnerability::Vulnerable => char::from(value.character).to_ascii_lowercase(),
+                    Vulnerability::Eaten => '"', // Only the eyes are left.
                 }
             }
         }
@@ -104,6 +160,19 @@ impl CharacterData {
         Self{ vulnerability: Vulnerability::Invulnerable, ghost_mode:GhostMode::Scatter, facing_direction: Direction::right(), nav_path: vec![], character, position, scatter_position }
     }
 
+    /// Creates and initializes new character data that starts at the provided grid's spawn point.
+    pub fn spawn(character: Character, grid: &Grid) -> Self {
+        let mut res = Self::new(character);
+        res.position = grid.get_spawn(character);
+        res.scatter_position = grid.get_scatter_position(character);
+        res
+    }
+
+    /// Gets the position the character goes to during scatter mode.
+    pub fn get_scatter_position(&self) -> Vector2 {
+        self.scatter_position
+    }
+
     /// Sets the position of the character.
     pub fn set_position(&mut self, position: Vector2) {
         self.position = position;
@@ -121,10 +190,8 @@ impl CharacterData {
 
     /// Sets the direction of the character only if they are allowed to move in the new direction.
     pub fn set_direction_if_valid(&mut self, direction: Direction, grid: &Grid) {
-        let old = self.get_direction();
-        self.set_direction(direction);
-        if !grid.is_valid_pos(&self.calculate_facing_position()) {
-            self.set_direction(old);
+        if grid.can_move(&self.position, direction, self.get_mover()) {
+            self.set_direction(direction);
         }
     }
 
@@ -138,6 +205,15 @@ impl CharacterData {
         self.character
     }
 
+    /// Gets what kind of mover the character is when deciding where they can go.
+    pub fn get_mover(&self) -> Mover {
+        match (self.character, self.vulnerability) {
+            (Character::Rucman, _) => Mover::Rucman,
+            (_, Vulnerability::Eaten) => Mover::EatenGhost,
+            _ => Mover::Ghost,
+        }
+    }
+
     /// Calculates a position 1 unit away in relation to the character's current direction.
     pub fn calculate_facing_position(&self) -> Vector2 {
         let offset = {
@@ -150,14 +226,21 @@ impl CharacterData {
         self.position + offset
     }
 
-    /// Moves in the direction the character is currently facing.
+    /// Moves in the direction the character is currently facing, going through any teleporter stepped on.
     pub fn rucman_move(&mut self, grid: &Grid) {
-        let next_pos = self.calculate_facing_position();
-        if grid.is_valid_pos(&next_pos) { self.set_position(next_pos) };
+        if let Some((next_pos, direction)) = grid.try_move(&self.position, self.facing_direction, self.get_mover()) {
+            self.set_position(next_pos);
+            self.set_direction(direction);
+        }
     }
 
-    /// Move based on the current ghost mode.
+    /// Move based on the current ghost mode, or home if the ghost has been eaten.
     pub fn ghost_move(&mut self, grid: &mut Grid, position: Vector2, rucman_direction: Direction) {
+        if self.vulnerability == Vulnerability::Eaten {
+            self.ghost_return(grid);
+            return;
+        }
+
         match self.ghost_mode {
             GhostMode::Chase => self.ghost_chase(grid, position, rucman_direction),
             GhostMode::Scatter => self.ghost_scatter(grid),
@@ -168,8 +251,8 @@ impl CharacterData {
     fn ghost_chase(&mut self, grid: &mut Grid, position: Vector2, rucman_direction: Direction) {
         // Only update path if remaining path is short or you're close to provided position.
         // So the ghosts aren't as relentless in their chases.
-        if self.nav_path.len() > 5 && Vector2::distance(self.position, position) > 5.0 {
-            let next = self.nav_path.pop().unwrap(); //Shouldn't be none since size is already checked.
+        if self.nav_path.len() > 5 && Vector2::distance(self.position, position) > 5.0
+            && let Some(next) = self.nav_path.pop() {
             self.set_position(next);
             return;
         }
@@ -213,13 +296,16 @@ impl CharacterData {
                     }
                 },
                 Character ::Clyde => { // Random wander.
-                    grid.get_random_position()
+                    grid.get_random_position().unwrap_or(self.position) // Stays put in a maze with nowhere to wander.
                 }
             }
         };
 
-        // Use A* to form a path
-        match a_star::a_star(&grid, self.position, target, true) {
+        // Aiming at a teleporter means aiming at where it leads.
+        let (target, _) = grid.follow_portal(target, rucman_direction);
+
+        // Look up the shortest path
+        match grid.distances(self.get_mover(), |table| table.path(self.position, target)) {
             Some(path) => {
                 self.nav_path = path;
                 let next = self.nav_path.pop();
@@ -227,21 +313,22 @@ impl CharacterData {
                     self.set_position(next.unwrap());
                 }
             },
-            None => { self.nav_path = Vec::new() }
+            None => {
+                warn!(ghost = %self.character, from = ?self.position, to = ?target, mode = ?self.ghost_mode, "No path found");
+                self.nav_path = Vec::new();
+            },
         }
     }
 
     /// Move towards the character's scatter point.
-    fn ghost_scatter(&mut self, grid: &Grid) {
+    fn ghost_scatter(&mut self, grid: &mut Grid) {
         if self.nav_path.is_empty() {
-            self.nav_path = match a_star::a_star(grid, self.position, self.scatter_position, true) {
-                Some(path) => path,
-                None => Vec::new(),
-            };
+            let path = grid.distances(self.get_mover(), |table| table.path(self.position, self.scatter_position));
+            if path.is_none() { warn!(ghost = %self.character, from = ?self.position, to = ?self.scatter_position, mode = ?self.ghost_mode, "No path found"); }
+            self.nav_path = path.unwrap_or_default();
         }
 
-        if !self.nav_path.is_empty() {
-            let next = self.nav_path.pop().unwrap();
+        if let Some(next) = self.nav_path.pop() {
             self.set_position(next);
         }
 
@@ -250,6 +337,40 @@ impl CharacterData {
         }
     }
 
+    /// Move towards the character's spawn, becoming invulnerable again once there.
+    fn ghost_return(&mut self, grid: &mut Grid) {
+        let home = grid.get_spawn(self.character);
+        if self.nav_path.is_empty() && self.position != home {
+            let path = grid.distances(self.get_mover(), |table| table.path(self.position, home));
+            if path.is_none() { warn!(ghost = %self.character, from = ?self.position, to = ?home, "No path home found"); }
+            self.nav_path = path.unwrap_or_default();
+        }
+
+        if let Some(next) = self.nav_path.pop() {
+            self.set_position(next);
+        }
+
+        // A ghost with no way home comes back to life where it is rather than wandering as eyes forever.
+        if self.nav_path.is_empty() {
+            self.set_invulnerable();
+        }
+    }
+
+    /// Gets the rest of the path the ghost is following, in reverse order so the next step is last.
+    pub fn get_nav_path(&self) -> &[Vector2] {
+        &self.nav_path
+    }
+
+    /// Gets the tile the ghost is heading for, which is the end of its path. None if it isn't going anywhere.
+    pub fn get_target(&self) -> Option<Vector2> {
+        self.nav_path.first().copied()
+    }
+
+    /// Gets the current ghost mode.
+    pub fn get_ghost_mode(&self) -> GhostMode {
+        self.ghost_mode
+    }
+
     /// Set ghost mode to Scatter if in Chase mode and vise versa.
     pub fn toggle_ghost_mode(&mut self) {
         match self.ghost_mode {
@@ -273,7 +394,7 @@ impl CharacterData {
     /// Makes the ghost Vulnerable if they are Invulnerable and vise versa.
     pub fn toggle_vulnerability(&mut self) {
         match self.vulnerability {
-            Vulnerability::Vulnerable => self.set_invulnerable(),
+            Vulnerability::Vulnerable | Vulnerability::Eaten => self.set_invulnerable(),
             Vulnerability::Invulnerable => self.set_vulnerable(),
         }
     }
@@ -290,6 +411,17 @@ impl CharacterData {
         self.vulnerability = Vulnerability::Invulnerable;
     }
 
+    /// Makes the ghost head home after being eaten.
+    pub fn set_eaten(&mut self) {
+        self.nav_path.clear();
+        self.vulnerability = Vulnerability::Eaten;
+    }
+
+    /// Makes the ghost vulnerable unless it's already been eaten and is on its way home.
+    pub fn frighten(&mut self) {
+        if self.vulnerability != Vulnerability::Eaten { self.set_vulnerable(); }
+    }
+
     /// Gets the current vulnerability of the ghost.
     pub fn get_vulnerability(&self) -> Vulnerability {
         self.vulnerability
@@ -299,6 +431,7 @@ impl CharacterData {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::grid::grid::Portal;
 
     /// Tests if character data creation works properly.
     #[test]
@@ -310,6 +443,70 @@ mod test {
         assert_eq!(CharacterData::new(Character::Clyde), CharacterData{position: Vector2(14, 11), scatter_position: Vector2(1, 25), nav_path: vec![], facing_direction: Direction::right(), vulnerability: Vulnerability::Invulnerable, ghost_mode: GhostMode::Scatter, character: Character::Clyde});
     }
 
+    /// Tests if Elroy stages grant extra steps at the right rate.
+    #[test]
+    fn test_elroy_extra_step() {
+        let extra_steps = |stage: ElroyStage| (0..16).filter(|frame| stage.is_extra_step(*frame)).count();
+        assert_eq!(extra_steps(ElroyStage::Inactive), 0);
+        assert_eq!(extra_steps(ElroyStage::One), 2);
+        assert_eq!(extra_steps(ElroyStage::Two), 4);
+    }
+
+    /// Tests if Rucman goes through teleporters and leaves in the right direction.
+    #[test]
+    fn test_rucman_teleports() {
+        let mut grid = Grid::new();
+        let mut rucman = CharacterData::new(Character::Rucman);
+        rucman.set_position(Vector2(25, 12));
+        rucman.rucman_move(&grid);
+        assert_eq!(rucman.get_position(), Vector2(0, 12));
+        rucman.rucman_move(&grid);
+        assert_eq!(rucman.get_position(), Vector2(1, 12));
+
+        grid.add_portal(Vector2(2, 12), Portal { destination: Vector2(6, 4), exit: Some(Direction::up()), name: None }).unwrap();
+        rucman.rucman_move(&grid);
+        assert_eq!((rucman.get_position(), rucman.get_direction()), (Vector2(6, 4), Direction::up()));
+        rucman.rucman_move(&grid);
+        assert_eq!(rucman.get_position(), Vector2(6, 3));
+    }
+
+    /// Tests if Rucman can't turn towards or walk through doors.
+    #[test]
+    fn test_rucman_blocked_by_doors() {
+        let grid = Grid::from(vec![
+            "#####".chars().collect(),
+            "#.-.#".chars().collect(),
+            "#####".chars().collect(),
+        ]);
+        let mut rucman = CharacterData::new(Character::Rucman);
+        rucman.set_position(Vector2(1, 1));
+        rucman.set_direction(Direction::up());
+        rucman.set_direction_if_valid(Direction::right(), &grid);
+        assert_eq!(rucman.get_direction(), Direction::up());
+
+        rucman.set_direction(Direction::right());
+        rucman.rucman_move(&grid);
+        assert_eq!(rucman.get_position(), Vector2(1, 1));
+
+        let mut blinky = CharacterData::new(Character::Blinky);
+        blinky.set_position(Vector2(1, 1));
+        blinky.set_direction_if_valid(Direction::right(), &grid);
+        assert_eq!(blinky.get_direction(), Direction::right());
+    }
+
+    /// Tests if ghosts path through teleporters when it's shorter.
+    #[test]
+    fn test_ghost_teleports() {
+        let mut grid = Grid::new();
+        let mut blinky = CharacterData::new(Character::Blinky);
+        blinky.set_chase_mode();
+        blinky.set_position(Vector2(24, 12));
+        blinky.ghost_move(&mut grid, Vector2(2, 12), Direction::left());
+        assert_eq!(blinky.get_position(), Vector2(25, 12));
+        blinky.ghost_move(&mut grid, Vector2(2, 12), Direction::left());
+        assert_eq!(blinky.get_position(), Vector2(0, 12));
+    }
+
     /// Tests if character's position is set properly.
     #[test]
     fn test_set_position() {
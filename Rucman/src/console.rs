@@ -0,0 +1,222 @@
+use crate::character::{Character, GhostMode};
+use crate::debugger::inspect;
+use crate::error::{Result, RucmanError};
+use crate::game::Game;
+use crate::point::Vector2;
+
+/// Every command the console understands, as shown by `help`.
+pub const COMMANDS: [&str; 11] = [
+    "god - Toggle whether ghosts can catch Rucman",
+    "give lives N | give score N",
+    "level N - Jump to a level with its timers",
+    "teleport X Y - Move Rucman",
+    "ghost NAME scatter|chase",
+    "frighten FRAMES - Make every ghost vulnerable",
+    "eat-all - Eat every pellet to clear the level",
+    "seed - Show the game's seed",
+    "dump state - Show every character, the grid and the numbers",
+    "clear - Clear the console",
+    "help - Show this list",
+];
+
+/// How many lines of output the console keeps.
+const HISTORY: usize = 12;
+
+/// A command line for changing the game while it's being played, for testing things that take a long time to
+/// reach such as late levels.
+#[derive(Debug, Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    output: Vec<String>, // The commands run and what they gave back, oldest first.
+}
+
+impl Console {
+    /// Creates a closed console.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the console is showing and taking key presses.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the console if it's closed and closes it if it's open.
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Adds a typed character to the command.
+    pub fn type_char(&mut self, typed: char) {
+        self.input.push(typed);
+    }
+
+    /// Removes the last character of the command.
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Runs the typed command on the game and records what happened.
+    pub fn submit(&mut self, game: &mut Game) {
+        let command = std::mem::take(&mut self.input);
+        if command.trim() == "clear" {
+            self.output.clear();
+            return;
+        }
+
+        self.output.push(format!("> {command}"));
+        match execute(game, &command) {
+            Ok(lines) => self.output.extend(lines),
+            Err(err) => self.output.push(format!("Error: {err}")),
+        }
+
+        let excess = self.output.len().saturating_sub(HISTORY);
+        self.output.drain(..excess);
+    }
+
+    /// Retrieves the lines to draw: the recent output followed by the command being typed.
+    pub fn lines(&self) -> Vec<String> {
+        let mut res = self.output.clone();
+        res.push(format!("> {}_", self.input));
+        res
+    }
+}
+
+/// Runs a console command on the game, retrieving what to show for it.
+pub fn execute(game: &mut Game, command: &str) -> Result<Vec<String>> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let number = |index: usize| -> Result<i64> {
+        let word = words.get(index).ok_or_else(|| RucmanError::InvalidConfig(format!("'{command}' is missing a number")))?;
+        word.parse().map_err(|_| RucmanError::InvalidConfig(format!("'{word}' isn't a number")))
+    };
+    let bad_number = |value: i64| RucmanError::InvalidConfig(format!("{value} is out of range"));
+
+    let res = match words.as_slice() {
+        [] => Vec::new(),
+        ["god"] => {
+            game.set_god_mode(!game.is_god_mode());
+            vec![format!("God mode {}", if game.is_god_mode() { "on" } else { "off" })]
+        },
+        ["give", "lives", _] => {
+            let lives = number(2)?;
+            game.get_number_manager_mut().add_lives(u8::try_from(lives).map_err(|_| bad_number(lives))?);
+            vec![format!("Lives: {}", game.get_number_manager().get_lives())]
+        },
+        ["give", "score", _] => {
+            let score = number(2)?;
+            game.get_number_manager_mut().add_score(u32::try_from(score).map_err(|_| bad_number(score))?);
+            vec![format!("Score: {}", game.get_number_manager().get_score())]
+        },
+        ["level", _] => {
+            let level = number(1)?;
+            game.warp_to_level(u32::try_from(level).map_err(|_| bad_number(level))?)?;
+            vec![format!("Level {level}")]
+        },
+        ["teleport", _, _] => {
+            let (x, y) = (number(1)?, number(2)?);
+            let pos = Vector2(i32::try_from(x).map_err(|_| bad_number(x))?, i32::try_from(y).map_err(|_| bad_number(y))?);
+            game.teleport(pos)?;
+            vec![format!("Rucman is at {x},{y}")]
+        },
+        ["ghost", name, mode] => {
+            let character = Character::from_name(name).filter(|character| *character != Character::Rucman)
+                .ok_or_else(|| RucmanError::InvalidConfig(format!("No ghost is called '{name}'")))?;
+            let mode = match *mode {
+                "scatter" => GhostMode::Scatter,
+                "chase" => GhostMode::Chase,
+                _ => return Err(RucmanError::InvalidConfig(format!("Ghosts can scatter or chase, not '{mode}'"))),
+            };
+            game.set_ghost_mode(character, mode)?;
+            vec![format!("{character}: {mode:?}")]
+        },
+        ["frighten", _] => {
+            let frames = number(1)?;
+            game.frighten(u32::try_from(frames).map_err(|_| bad_number(frames))?);
+            vec![format!("Ghosts are frightened for {frames} frames")]
+        },
+        ["eat-all"] => {
+            game.eat_all();
+            vec![String::from("Every pellet is eaten")]
+        },
+        ["seed"] => vec![format!("Seed: {}", game.get_seed())],
+        ["dump", "state"] => inspect(game),
+        ["help"] => COMMANDS.iter().map(|line| line.to_string()).collect(),
+        _ => return Err(RucmanError::InvalidConfig(format!("Unknown command '{command}', try help"))),
+    };
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Vulnerability;
+    use crate::game::StepOutcome;
+    use crate::level::LevelPack;
+    use crate::managers::NumberManager;
+
+    /// Tests if each command changes the game the way it says.
+    #[test]
+    fn commands() {
+        let mut game = Game::with_seed(LevelPack::classic(), 4).unwrap();
+        let lives = game.get_number_manager().get_lives();
+        execute(&mut game, "give lives 5").unwrap();
+        assert_eq!(game.get_number_manager().get_lives(), lives + 5);
+
+        execute(&mut game, "teleport 1 1").unwrap();
+        assert_eq!(game.get_rucman().get_position(), Vector2(1, 1));
+        assert!(execute(&mut game, "teleport 0 0").is_err());
+
+        execute(&mut game, "ghost blinky chase").unwrap();
+        let blinky = game.get_ghosts().iter().find(|ghost| ghost.get_character() == Character::Blinky).unwrap();
+        assert_eq!(blinky.get_ghost_mode(), GhostMode::Chase);
+        assert!(execute(&mut game, "ghost rucman chase").is_err());
+
+        execute(&mut game, "frighten 60").unwrap();
+        assert_eq!(game.get_number_manager().get_vulnerability_timer(), 60);
+        assert!(game.get_ghosts().iter().all(|ghost| ghost.get_vulnerability() == Vulnerability::Vulnerable));
+
+        assert_eq!(execute(&mut game, "seed").unwrap(), vec![String::from("Seed: 4")]);
+        assert_eq!(execute(&mut game, "dump state").unwrap(), inspect(&game));
+        assert!(execute(&mut game, "give lives lots").is_err());
+        assert!(execute(&mut game, "fly").is_err());
+
+        execute(&mut game, "give score 4294967295").unwrap();
+        game.step();
+        assert_eq!(game.get_number_manager().get_score(), u32::MAX);
+
+        execute(&mut game, "eat-all").unwrap();
+        assert_eq!(game.step(), StepOutcome::LevelComplete);
+    }
+
+    /// Tests if jumping to a level gives it the same timers as clearing every level before it, keeping the score.
+    #[test]
+    fn level_jump() {
+        let mut game = Game::with_seed(LevelPack::classic(), 4).unwrap();
+        execute(&mut game, "give score 500").unwrap();
+        execute(&mut game, "level 7").unwrap();
+
+        let mut expected = NumberManager::new();
+        for _ in 1..7 {
+            expected.advance_level();
+        }
+        let number_manager = game.get_number_manager();
+        assert_eq!(number_manager.get_level(), 7);
+        assert_eq!(number_manager.get_score(), 500);
+        assert_eq!(number_manager.get_scatter_interval(), expected.get_scatter_interval());
+        assert_eq!(game.get_grid().pellets_left(), game.get_grid().get_starting_pellets());
+        assert!(execute(&mut game, "level 0").is_err());
+    }
+
+    /// Tests if ghosts pass through Rucman in god mode.
+    #[test]
+    fn god_mode() {
+        let mut game = Game::with_seed(LevelPack::classic(), 4).unwrap();
+        execute(&mut game, "god").unwrap();
+        for _ in 0..500 {
+            assert!(!matches!(game.step(), StepOutcome::Caught(_)));
+        }
+        assert_eq!(execute(&mut game, "god").unwrap(), vec![String::from("God mode off")]);
+    }
+}
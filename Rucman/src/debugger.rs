@@ -0,0 +1,275 @@
+use crate::character::CharacterData;
+use crate::direction::Direction;
+use crate::error::{Result, RucmanError};
+use crate::game::{Game, StepOutcome};
+use crate::grid::grid::Mover;
+
+/// Plays a single tick the way the game loop does: turns Rucman if there's an input, steps, then respawns him if
+/// he was caught or loads the next level if he cleared this one. Fails once the game is over or won, the same as
+/// the game loop stopping.
+pub fn tick(game: &mut Game, input: Option<Direction>) -> Result<StepOutcome> {
+    if game.is_over() || game.is_won() {
+        return Err(RucmanError::InvalidConfig(format!("The game ended at frame {}", game.get_frames())));
+    }
+    if let Some(direction) = input { game.turn(direction); }
+
+    let outcome = game.step();
+    match outcome {
+        StepOutcome::Caught(_) => game.respawn(),
+        StepOutcome::LevelComplete => { game.next_level()?; },
+        StepOutcome::Continue => {},
+    }
+
+    Ok(outcome)
+}
+
+/// Records a game tick by tick so it can be stepped through and rewound to any earlier tick.
+///
+/// Only a snapshot every so many ticks is kept, along with the input of every tick. Going back to a tick starts
+/// from the snapshot before it and replays the inputs, which the game being deterministic makes exact.
+#[derive(Debug)]
+pub struct Timeline {
+    game: Game,
+    tick: usize,
+    inputs: Vec<Option<Direction>>, // The input of every tick recorded so far.
+    snapshots: Vec<Game>, // The game at every multiple of the interval, before that tick's input.
+    interval: usize,
+}
+
+impl Timeline {
+    /// Starts recording the provided game, keeping a snapshot every interval ticks.
+    pub fn new(game: Game, interval: usize) -> Self {
+        Timeline { snapshots: vec![game.clone()], game, tick: 0, inputs: Vec::new(), interval: interval.max(1) }
+    }
+
+    /// Retrieves the game at the current tick.
+    pub fn get_game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Retrieves the current tick.
+    pub fn get_tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Retrieves how many ticks have been recorded, which can be past the current tick after rewinding.
+    pub fn get_length(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns true once the game is over or won, after which there are no more ticks to play.
+    pub fn is_finished(&self) -> bool {
+        self.game.is_over() || self.game.is_won()
+    }
+
+    /// Retrieves how many ticks apart snapshots are.
+    pub fn get_interval(&self) -> usize {
+        self.interval
+    }
+
+    /// Plays the next tick. Without an input, a tick that was already recorded is played again the same way.
+    /// A new input after rewinding replaces everything recorded from the current tick on.
+    /// Fails without recording anything once the game is over or won.
+    pub fn advance(&mut self, input: Option<Direction>) -> Result<StepOutcome> {
+        if self.is_finished() {
+            return Err(RucmanError::InvalidConfig(format!("The game ended at tick {}", self.tick)));
+        }
+
+        let input = match self.inputs.get(self.tick) {
+            Some(recorded) if input.is_none() || input == *recorded => *recorded,
+            _ => {
+                self.inputs.truncate(self.tick);
+                self.snapshots.truncate(self.tick / self.interval + 1);
+                self.inputs.push(input);
+                input
+            },
+        };
+
+        let outcome = tick(&mut self.game, input)?;
+        self.tick += 1;
+        if self.tick.is_multiple_of(self.interval) && self.snapshots.len() == self.tick / self.interval {
+            self.snapshots.push(self.game.clone());
+        }
+        else { self.check_snapshot()?; }
+
+        Ok(outcome)
+    }
+
+    /// Goes to any tick that's been recorded, earlier or later than the current one. Fails if replaying reaches a
+    /// snapshot that the game doesn't match.
+    pub fn seek(&mut self, target: usize) -> Result<()> {
+        if target > self.get_length() {
+            return Err(RucmanError::InvalidConfig(format!("Tick {target} hasn't been played, the last is {}", self.get_length())));
+        }
+
+        // Replay from the closest snapshot, unless going forward from the current tick is closer.
+        let snapshot = target / self.interval;
+        if target < self.tick || snapshot * self.interval > self.tick {
+            self.game = self.snapshots[snapshot].clone();
+            self.tick = snapshot * self.interval;
+        }
+        while self.tick < target {
+            tick(&mut self.game, self.inputs[self.tick])?;
+            self.tick += 1;
+            self.check_snapshot()?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure a replayed game matches the snapshot taken at the current tick, if there is one.
+    fn check_snapshot(&self) -> Result<()> {
+        if !self.tick.is_multiple_of(self.interval) { return Ok(()); }
+        let Some(snapshot) = self.snapshots.get(self.tick / self.interval) else { return Ok(()); };
+
+        let (expected, found) = (inspect(snapshot), inspect(&self.game));
+        match expected.into_iter().zip(found).find(|(expected, found)| expected != found) {
+            Some((expected, found)) => Err(RucmanError::ReplayMismatch { frame: snapshot.get_frames(), expected, found }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Describes everything about the game's state, a line per character followed by the grid and the numbers.
+pub fn inspect(game: &Game) -> Vec<String> {
+    let describe = |character: &CharacterData| {
+        let pos = character.get_position();
+        let mut res = format!("{}: {},{} facing {}", character.get_character(), pos.0, pos.1, character.get_direction());
+        if character.get_mover() != Mover::Rucman {
+            let scatter = character.get_scatter_position();
+            res.push_str(&format!(" | {:?} {:?} | scatter {},{} | path {}", character.get_ghost_mode(), character.get_vulnerability(),
+                scatter.0, scatter.1, character.get_nav_path().len()));
+            if let Some(target) = character.get_target() { res.push_str(&format!(" to {},{}", target.0, target.1)); }
+        }
+        res
+    };
+
+    let grid = game.get_grid();
+    let number_manager = game.get_number_manager();
+    let mut res: Vec<String> = [game.get_rucman()].into_iter().chain(game.get_ghosts()).map(describe).collect();
+    res.push(format!(
+        "Grid: {}x{} | {}/{} pellets left | {} open spaces",
+        grid.get_width(), grid.get_height(), grid.pellets_left(), grid.get_starting_pellets(), grid.get_open_space_count(),
+    ));
+    res.push(format!(
+        "Score {} | Lives {} | Level {} | One up at {} | Frightened {} | Scatter every {} | Elroy {:?} | Frame {}",
+        number_manager.get_score(), number_manager.get_lives(), number_manager.get_level(), number_manager.get_one_up_score(),
+        number_manager.get_vulnerability_timer(), number_manager.get_scatter_interval(),
+        number_manager.get_elroy_stage(grid.pellets_left()), game.get_frames(),
+    ));
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LevelPack;
+
+    /// Plays the inputs on a fresh game, describing the game after every tick.
+    fn play(inputs: &[Option<Direction>]) -> Vec<Vec<String>> {
+        let mut game = Game::with_seed(LevelPack::classic(), 6).unwrap();
+        let mut res = vec![inspect(&game)];
+        for input in inputs {
+            tick(&mut game, *input).unwrap();
+            res.push(inspect(&game));
+        }
+        res
+    }
+
+    /// Tests if rewinding to any tick, or going forward again, gives exactly the state the game had then.
+    #[test]
+    fn rewind_matches_playing() {
+        let directions = Direction::directions();
+        let inputs: Vec<Option<Direction>> = (0..80).map(|tick| if tick % 7 == 0 { Some(directions[tick / 7 % 4]) } else { None }).collect();
+        let expected = play(&inputs);
+
+        let mut timeline = Timeline::new(Game::with_seed(LevelPack::classic(), 6).unwrap(), 25);
+        for input in &inputs {
+            timeline.advance(*input).unwrap();
+        }
+        assert_eq!(inspect(timeline.get_game()), expected[80]);
+
+        for tick in [79, 0, 60, 75, 74, 80, 26] {
+            timeline.seek(tick).unwrap();
+            assert_eq!(timeline.get_tick(), tick);
+            assert_eq!(inspect(timeline.get_game()), expected[tick], "Tick {tick} differs");
+        }
+        assert!(timeline.seek(81).is_err());
+
+        // Stepping without an input replays the recording.
+        timeline.advance(None).unwrap();
+        assert_eq!(inspect(timeline.get_game()), expected[27]);
+        assert_eq!(timeline.get_length(), 80);
+    }
+
+    /// Tests if replaying past a snapshot the game doesn't match fails instead of showing the wrong game.
+    #[test]
+    fn replay_mismatch() {
+        let mut timeline = Timeline::new(Game::with_seed(LevelPack::classic(), 6).unwrap(), 10);
+        for _ in 0..25 {
+            timeline.advance(None).unwrap();
+        }
+
+        timeline.snapshots[2].teleport(crate::point::Vector2(1, 1)).unwrap();
+        timeline.seek(15).unwrap();
+        for _ in 15..19 {
+            timeline.advance(None).unwrap();
+        }
+        let err = timeline.advance(None).unwrap_err();
+        assert!(matches!(err, RucmanError::ReplayMismatch { frame: 20, .. }), "{err}");
+        assert!(err.to_string().starts_with("Replay went differently at frame 20: expected Rucman: 1,1"));
+    }
+
+    /// Tests if the timeline stops once the last level is cleared or Rucman is out of lives.
+    #[test]
+    fn stops_when_finished() {
+        let pack = LevelPack::parse("[level]\nmaze = mazes/lattice.maze\n", std::path::Path::new("")).unwrap();
+        let mut game = Game::with_seed(pack, 6).unwrap();
+        game.eat_all();
+        let mut timeline = Timeline::new(game, 10);
+        assert_eq!(timeline.advance(None).unwrap(), StepOutcome::LevelComplete);
+        assert!(timeline.is_finished() && timeline.get_game().is_won());
+
+        let score = timeline.get_game().get_number_manager().get_score();
+        assert!(timeline.advance(None).is_err());
+        assert!(timeline.advance(Some(Direction::up())).is_err());
+        assert_eq!(timeline.get_length(), 1);
+        assert_eq!(timeline.get_game().get_number_manager().get_score(), score);
+
+        timeline.seek(0).unwrap(); // Rewinding can still play on.
+        assert!(!timeline.is_finished());
+
+        let mut game = Game::with_seed(LevelPack::classic(), 6).unwrap();
+        for _ in 0..3 {
+            game.get_number_manager_mut().lose_life();
+        }
+        let mut timeline = Timeline::new(game, 10);
+        assert!(timeline.is_finished());
+        assert!(timeline.advance(None).is_err());
+    }
+
+    /// Tests if a new input after rewinding starts a new recording from there.
+    #[test]
+    fn new_input_branches() {
+        let mut timeline = Timeline::new(Game::with_seed(LevelPack::classic(), 6).unwrap(), 10);
+        for _ in 0..40 {
+            timeline.advance(None).unwrap();
+        }
+
+        timeline.seek(15).unwrap();
+        timeline.advance(Some(Direction::left())).unwrap();
+        assert_eq!(timeline.get_length(), 16);
+        for _ in 0..30 {
+            timeline.advance(None).unwrap();
+        }
+
+        let mut inputs = vec![None; 46];
+        inputs[15] = Some(Direction::left());
+        let expected = play(&inputs);
+        for tick in [46, 12, 16, 31] {
+            timeline.seek(tick).unwrap();
+            assert_eq!(inspect(timeline.get_game()), expected[tick], "Tick {tick} differs");
+        }
+    }
+}
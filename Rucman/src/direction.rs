@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use crate::point::Vector2;
 
 /// Denotes a direction an entity can go on the grid.
@@ -39,4 +41,32 @@ impl Direction {
             Direction::right(),
         ]
     }
-}
\ No newline at end of file
+
+    /// Returns the direction facing the other way.
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Up(_) => Direction::down(),
+            Direction::Down(_) => Direction::up(),
+            Direction::Left(_) => Direction::right(),
+            Direction::Right(_) => Direction::left(),
+        }
+    }
+
+    /// Finds the direction with the provided name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::directions().into_iter().find(|direction| direction.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match *self {
+            Direction::Up(_) => "up",
+            Direction::Down(_) => "down",
+            Direction::Left(_) => "left",
+            Direction::Right(_) => "right",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::ControlFlow;
+
+use crate::direction::Direction;
+use crate::grid::grid::{Grid, Mover};
+use crate::point::Vector2;
+
+/// How many distance fields are kept before the table starts over. Mazes with fewer open spaces than this
+/// get every field up front.
+const MAX_FIELDS: usize = 1024;
+
+/// Marks a position that can't reach the target.
+const UNREACHABLE: u16 = u16::MAX;
+
+/// Stores how far every open space of a maze is from the targets a mover heads for, so the next step
+/// towards a target is a lookup instead of a search.
+///
+/// Each target gets a field built by a breadth-first search backwards from it, following the same moves
+/// `Grid::try_move` allows. Fields are made the first time a target is asked for and kept for later.
+#[derive(Debug, Clone)]
+pub struct DistanceTable {
+    width: usize,
+    ids: Vec<Option<usize>>, // The id of every position in the maze, by row then column. Walls don't have one.
+    positions: Vec<Vector2>, // The position of every id.
+    successors: Vec<Vec<usize>>, // Where the mover can get to in one step from every id, in direction order.
+    predecessors: Vec<Vec<usize>>, // Where the mover can get to every id from in one step.
+    fields: HashMap<usize, Vec<u16>>, // Distances to a target id from every id.
+}
+
+impl DistanceTable {
+    /// Creates the distance table of a maze for the provided mover.
+    pub fn new(grid: &Grid, mover: Mover) -> Self {
+        let width = grid.get_width();
+        let mut ids = Vec::new();
+        let mut positions = Vec::new();
+        for row in 0..grid.get_height() {
+            for col in 0..width {
+                let pos = Vector2(col as i32, row as i32);
+                if grid.is_valid_pos(&pos) {
+                    ids.push(Some(positions.len()));
+                    positions.push(pos);
+                }
+                else {
+                    ids.push(None);
+                }
+            }
+        }
+
+        let mut res = DistanceTable {
+            width,
+            successors: vec![Vec::new(); positions.len()],
+            predecessors: vec![Vec::new(); positions.len()],
+            fields: HashMap::new(),
+            ids,
+            positions,
+        };
+
+        for id in 0..res.positions.len() {
+            for direction in Direction::directions() {
+                let Some((next, _)) = grid.try_move(&res.positions[id], direction, mover) else { continue; };
+                let Some(next) = res.get_id(next) else { continue; };
+                if !res.successors[id].contains(&next) {
+                    res.successors[id].push(next);
+                    res.predecessors[next].push(id);
+                }
+            }
+        }
+
+        if res.positions.len() <= MAX_FIELDS {
+            for target in 0..res.positions.len() {
+                res.build_field(target);
+            }
+        }
+
+        res
+    }
+
+    /// Retrieves how many steps it takes to get from one position to another, if it can be done at all.
+    pub fn distance(&mut self, from: Vector2, to: Vector2) -> Option<u32> {
+        let (from, to) = (self.get_id(from)?, self.get_id(to)?);
+        match self.field(to)[from] {
+            UNREACHABLE => None,
+            distance => Some(distance as u32),
+        }
+    }
+
+    /// Retrieves the position one step closer to the target. Ties go to the first direction in
+    /// `Direction::directions`. Returns None if the target can't be reached or the mover is already there.
+    pub fn next_step(&mut self, from: Vector2, to: Vector2) -> Option<Vector2> {
+        let (from, to) = (self.get_id(from)?, self.get_id(to)?);
+        self.next_id(from, to).map(|next| self.positions[next])
+    }
+
+    /// Finds the shortest path from the start to the end. Returns the path in the same form as `a_star` with
+    /// the start already stood on: in reverse order, without the start, so use pop to navigate it.
+    pub fn path(&mut self, from: Vector2, to: Vector2) -> Option<Vec<Vector2>> {
+        let (mut current, to) = (self.get_id(from)?, self.get_id(to)?);
+        if self.field(to)[current] == UNREACHABLE { return None; }
+
+        let mut path = Vec::new();
+        while let Some(next) = self.next_id(current, to) {
+            path.push(self.positions[next]);
+            current = next;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Finds the neighbour of an id that's closest to the target.
+    fn next_id(&mut self, from: usize, to: usize) -> Option<usize> {
+        self.field(to);
+        let field = &self.fields[&to];
+        if from == to || field[from] == UNREACHABLE { return None; }
+
+        // Successors that can't reach the target are UNREACHABLE, which mustn't wrap around to 0.
+        self.successors[from].iter().copied().find(|next| field[*next].checked_add(1) == Some(field[from]))
+    }
+
+    /// Retrieves the distance field of the provided target, building it if it hasn't been yet.
+    fn field(&mut self, target: usize) -> &Vec<u16> {
+        if !self.fields.contains_key(&target) {
+            if self.fields.len() >= MAX_FIELDS { self.fields.clear(); } // Keeps huge mazes from using up all the memory.
+            self.build_field(target);
+        }
+
+        &self.fields[&target]
+    }
+
+    /// Builds the distance field of the provided target by searching backwards from it.
+    fn build_field(&mut self, target: usize) {
+        let mut field = vec![UNREACHABLE; self.positions.len()];
+        field[target] = 0;
+
+        let mut frontier = VecDeque::from([target]);
+        while let Some(current) = frontier.pop_front() {
+            for previous in &self.predecessors[current] {
+                if field[*previous] == UNREACHABLE {
+                    field[*previous] = field[current].saturating_add(1);
+                    frontier.push_back(*previous);
+                }
+            }
+        }
+
+        self.fields.insert(target, field);
+    }
+
+    /// Retrieves the id of a position if it's an open space of the maze.
+    fn get_id(&self, pos: Vector2) -> Option<usize> {
+        if pos.0 < 0 || pos.1 < 0 || pos.0 as usize >= self.width { return None; }
+        *self.ids.get(pos.1 as usize * self.width + pos.0 as usize)?
+    }
+}
+
+/// Searches breadth first from the starts, telling `visit` about every position the mover reaches along with how
+/// many steps away it is and the direction of the first step from a start, which is None for the starts. `visit`
+/// decides whether to search on from the position, go no further that way or stop with a result.
+pub fn breadth_first<T>(grid: &Grid, starts: &[Vector2], mover: Mover,
+    mut visit: impl FnMut(Vector2, u32, Option<Direction>) -> ControlFlow<T, bool>) -> Option<T> {
+    let mut visited = vec![false; grid.get_width() * grid.get_height()];
+    let mut queue = VecDeque::new();
+    for start in starts {
+        let Some(cell) = grid.cell_id(start) else { continue; };
+        if visited[cell.index()] { continue; }
+        visited[cell.index()] = true;
+        match visit(*start, 0, None) {
+            ControlFlow::Break(res) => return Some(res),
+            ControlFlow::Continue(true) => queue.push_back((*start, 0, None)),
+            ControlFlow::Continue(false) => {},
+        }
+    }
+
+    while let Some((pos, steps, first)) = queue.pop_front() {
+        for direction in Direction::directions() {
+            let Some((next, _)) = grid.try_move(&pos, direction, mover) else { continue; };
+            let Some(cell) = grid.cell_id(&next) else { continue; };
+            if visited[cell.index()] { continue; }
+            visited[cell.index()] = true;
+
+            let first = first.or(Some(direction));
+            match visit(next, steps + 1, first) {
+                ControlFlow::Break(res) => return Some(res),
+                ControlFlow::Continue(true) => queue.push_back((next, steps + 1, first)),
+                ControlFlow::Continue(false) => {},
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star::a_star;
+    use crate::character::Character;
+    use crate::generator::{generate, MazeConfig};
+
+    /// Tests if distances through the built-in maze's tunnel are found.
+    #[test]
+    fn distances() {
+        let mut table = DistanceTable::new(&Grid::new(), Mover::Ghost);
+        assert_eq!(table.distance(Vector2(1, 1), Vector2(1, 1)), Some(0));
+        assert_eq!(table.distance(Vector2(1, 1), Vector2(6, 1)), Some(5));
+        assert_eq!(table.distance(Vector2(24, 12), Vector2(2, 12)), Some(4));
+        assert_eq!(table.distance(Vector2(0, 0), Vector2(1, 1)), None);
+        assert_eq!(table.next_step(Vector2(25, 12), Vector2(2, 12)), Some(Vector2(0, 12)));
+        assert_eq!(table.next_step(Vector2(2, 12), Vector2(2, 12)), None);
+    }
+
+    /// Tests if one-way tiles make distances differ depending on the direction.
+    #[test]
+    fn one_way_distances() {
+        let grid = Grid::from(vec![
+            "#######".chars().collect(),
+            "#..>..#".chars().collect(),
+            "#.###.#".chars().collect(),
+            "#.....#".chars().collect(),
+            "#######".chars().collect(),
+        ]);
+        let mut table = DistanceTable::new(&grid, Mover::Rucman);
+        assert_eq!(table.distance(Vector2(1, 1), Vector2(5, 1)), Some(4));
+        assert_eq!(table.distance(Vector2(5, 1), Vector2(1, 1)), Some(8));
+        assert_eq!(table.path(Vector2(5, 1), Vector2(1, 1)).unwrap().len(), 8);
+    }
+
+    /// Tests if a step next to a cell that can't reach the target is found without overflowing.
+    #[test]
+    fn unreachable_successor() {
+        let grid = Grid::from(vec![
+            "#####".chars().collect(),
+            "##^##".chars().collect(),
+            "#...#".chars().collect(),
+            "#####".chars().collect(),
+        ]);
+        let mut table = DistanceTable::new(&grid, Mover::Ghost);
+        assert_eq!(table.distance(Vector2(2, 1), Vector2(3, 2)), None);
+        assert_eq!(table.next_step(Vector2(2, 2), Vector2(3, 2)), Some(Vector2(3, 2)));
+        assert_eq!(table.path(Vector2(1, 2), Vector2(3, 2)).unwrap().len(), 2);
+    }
+
+    /// Tests if a breadth-first search reports steps and first directions, stops early and doesn't search past
+    /// where it's told not to.
+    #[test]
+    fn breadth_first_search() {
+        let grid = Grid::new();
+        let found = breadth_first(&grid, &[Vector2(1, 1)], Mover::Rucman, |pos, steps, first| {
+            if pos == Vector2(6, 1) { ControlFlow::Break((steps, first)) } else { ControlFlow::Continue(true) }
+        });
+        assert_eq!(found, Some((5, Some(Direction::right()))));
+
+        let mut reached = 0;
+        let none: Option<()> = breadth_first(&grid, &[Vector2(1, 1), Vector2(1, 1)], Mover::Rucman, |_, steps, _| {
+            reached += 1;
+            ControlFlow::Continue(steps < 2)
+        });
+        assert_eq!((none, reached), (None, 5));
+    }
+
+    /// Tests if paths are at least as short as the ones A* finds, on the built-in maze and a big generated one.
+    #[test]
+    fn paths_match_a_star() {
+        let big = generate(&MazeConfig { width: 61, height: 61, tunnels: 2 }, 7).unwrap();
+        for grid in [Grid::new(), big] {
+            let mut table = DistanceTable::new(&grid, Mover::Ghost);
+            let start = grid.get_spawn(Character::Rucman);
+            for end in [Vector2(1, 1), grid.get_spawn(Character::Blinky), start] {
+                let a_star_path = a_star(&grid, start, end, Mover::Ghost, true).unwrap();
+                let path = table.path(start, end).unwrap();
+                assert!(path.len() <= a_star_path.len());
+                assert_eq!(path.first().copied().unwrap_or(start), end);
+            }
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use crossterm::cursor;
+use crossterm::event::{read, Event, KeyCode, KeyModifiers};
+use crossterm::execute;
+use crossterm::style::Print;
+use crossterm::terminal::{Clear, ClearType};
+
+use std::fs;
+use std::io::{self, Stdout, stdout};
+use std::path::Path;
+
+use crate::character::Character;
+use crate::direction::Direction;
+use crate::point::Vector2;
+use crate::terminal::TerminalGuard;
+use crate::validate::{validate_maze_text, Diagnostic, Severity};
+
+/// Every tile the editor can place, as written in maze files. Teleporters are placed with `t` and pick their own digit.
+const TILES: [char; 16] = ['#', '.', '*', ' ', '-', '~', '_', '^', 'v', '<', '>', 'R', 'B', 'P', 'I', 'C'];
+
+/// Stores a maze being edited in the maze file format along with the editor's state.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    header: Vec<String>, // Comment and directive lines, kept as they were.
+    rows: Vec<Vec<char>>,
+    cursor: Vector2,
+    mirror: bool,
+    painting: bool,
+    last_tile: char,
+    undo_stack: Vec<Vec<Vec<char>>>,
+    redo_stack: Vec<Vec<Vec<char>>>,
+    diagnostics: Vec<Diagnostic>,
+    padded_rows: Vec<(usize, usize)>, // Rows that were filled out with walls when loaded, with their widths.
+}
+
+impl Editor {
+    /// Creates an empty maze surrounded by walls.
+    pub fn new(width: usize, height: usize) -> Self {
+        let width = width.max(3);
+        let height = height.max(3);
+        let mut rows = vec![vec![' '; width]; height];
+        for (row_num, row) in rows.iter_mut().enumerate() {
+            for (col_num, tile) in row.iter_mut().enumerate() {
+                if row_num == 0 || col_num == 0 || row_num == height - 1 || col_num == width - 1 { *tile = '#'; }
+            }
+        }
+
+        Self::from_parts(Vec::new(), rows, Vec::new())
+    }
+
+    /// Creates an editor for the text of a maze file. Walls are written as `#` and short rows are
+    /// filled out with walls so the maze can always be edited, with a warning for each one.
+    pub fn from_text(text: &str) -> Self {
+        let mut header = Vec::new();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() { continue; }
+
+            if line.starts_with(';') || line.starts_with('@') { header.push(line.to_string()); }
+            else { rows.push(line.chars().map(|tile| if tile == '█' { '#' } else { tile }).collect()); }
+        }
+
+        if rows.is_empty() { return Self::new(27, 31); }
+
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0); // Rows isn't empty.
+        let mut padded_rows = Vec::new();
+        for (row_num, row) in rows.iter_mut().enumerate() {
+            if row.len() != width { padded_rows.push((row_num, row.len())); }
+            row.resize(width, '#');
+        }
+
+        Self::from_parts(header, rows, padded_rows)
+    }
+
+    fn from_parts(header: Vec<String>, rows: Vec<Vec<char>>, padded_rows: Vec<(usize, usize)>) -> Self {
+        // Starts inside the outer wall unless the maze is too thin to have one.
+        let cursor = Vector2(1.min(rows[0].len() as i32 - 1), 1.min(rows.len() as i32 - 1));
+        let mut res = Editor {
+            header,
+            rows,
+            cursor,
+            mirror: false,
+            painting: false,
+            last_tile: '#',
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            diagnostics: Vec::new(),
+            padded_rows,
+        };
+        res.validate();
+        res
+    }
+
+    /// Writes the maze in the maze file format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.header {
+            text.push_str(line);
+            text.push('\n');
+        }
+        for row in &self.rows {
+            text.extend(row);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Retrieves the maze file tile at the provided position.
+    pub fn get_tile(&self, pos: Vector2) -> Option<char> {
+        if pos.0 < 0 || pos.1 < 0 { return None; }
+        self.rows.get(pos.1 as usize)?.get(pos.0 as usize).copied()
+    }
+
+    /// Gets the position of the cursor.
+    pub fn get_cursor(&self) -> Vector2 {
+        self.cursor
+    }
+
+    /// Borrow the problems found the last time the maze changed.
+    pub fn get_diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns true if placing tiles also places them mirrored across the center column.
+    pub fn is_mirroring(&self) -> bool {
+        self.mirror
+    }
+
+    /// Returns true if moving the cursor places the last placed tile.
+    pub fn is_painting(&self) -> bool {
+        self.painting
+    }
+
+    /// Turns mirrored placing on or off.
+    pub fn toggle_mirror(&mut self) {
+        self.mirror = !self.mirror;
+    }
+
+    /// Turns painting on or off. Turning it on paints the cursor's position straight away.
+    pub fn toggle_painting(&mut self) {
+        self.painting = !self.painting;
+        if self.painting { self.place(self.last_tile); }
+    }
+
+    /// Moves the cursor one step, staying inside the maze. Paints the new position while painting.
+    pub fn move_cursor(&mut self, direction: Direction) {
+        let next = self.cursor.forward(direction);
+        if self.get_tile(next).is_none() { return; }
+
+        self.cursor = next;
+        if self.painting { self.place(self.last_tile); }
+    }
+
+    /// Places a tile at the cursor and, while mirroring, at the mirrored position.
+    /// Spawns are never mirrored and placing one moves it from wherever it was.
+    /// `t` places a teleporter, finishing the pair of any teleporter that's missing its other end.
+    pub fn place(&mut self, tile: char) {
+        if tile != 't' && !TILES.contains(&tile) { return; }
+
+        let before = self.rows.clone();
+        let pos = self.cursor;
+        let mirrored = Vector2(self.rows[0].len() as i32 - 1 - pos.0, pos.1);
+        let is_spawn = Character::try_from(tile).is_ok();
+
+        if is_spawn {
+            for row in self.rows.iter_mut() {
+                for other in row.iter_mut() {
+                    if *other == tile { *other = ' '; }
+                }
+            }
+        }
+
+        let tile_to_place = if tile == 't' { self.next_teleporter_digit(pos) } else { tile };
+        self.set_tile(pos, tile_to_place);
+        if self.mirror && !is_spawn && mirrored != pos {
+            let mirrored_tile = match tile_to_place { // One-way tiles point the other way in the mirror.
+                '<' => '>',
+                '>' => '<',
+                _ => tile_to_place,
+            };
+            self.set_tile(mirrored, mirrored_tile);
+        }
+
+        self.last_tile = tile;
+        if self.rows != before {
+            self.undo_stack.push(before);
+            self.redo_stack.clear();
+            self.validate();
+        }
+    }
+
+    /// Undoes the last change to the maze.
+    pub fn undo(&mut self) {
+        if let Some(previous) = self.undo_stack.pop() {
+            self.redo_stack.push(std::mem::replace(&mut self.rows, previous));
+            self.validate();
+        }
+    }
+
+    /// Redoes the last undone change to the maze.
+    pub fn redo(&mut self) {
+        if let Some(next) = self.redo_stack.pop() {
+            self.undo_stack.push(std::mem::replace(&mut self.rows, next));
+            self.validate();
+        }
+    }
+
+    fn set_tile(&mut self, pos: Vector2, tile: char) {
+        if pos.0 < 0 || pos.1 < 0 { return; }
+        if let Some(current) = self.rows.get_mut(pos.1 as usize).and_then(|row| row.get_mut(pos.0 as usize)) {
+            *current = tile;
+        }
+    }
+
+    /// Finds the digit for a teleporter placed at the provided position. Reuses the digit of a teleporter
+    /// missing its other end, otherwise takes the lowest unused digit. Falls back to `9` once every digit is used.
+    fn next_teleporter_digit(&self, pos: Vector2) -> char {
+        let count = |digit: char| self.rows.iter().flatten().filter(|tile| **tile == digit).count();
+        if let Some(digit) = self.get_tile(pos).filter(|tile| tile.is_ascii_digit()) { return digit; }
+
+        ('0'..='9').find(|digit| count(*digit) == 1)
+            .or_else(|| ('0'..='9').find(|digit| count(*digit) == 0))
+            .unwrap_or('9')
+    }
+
+    fn validate(&mut self) {
+        self.diagnostics = validate_maze_text(&self.to_text());
+
+        let width = self.rows[0].len();
+        for (row_num, row_width) in &self.padded_rows {
+            let message = format!("Row was {row_width} wide and was filled out with walls to {width} when loaded");
+            self.diagnostics.push(Diagnostic::warning(Some(Vector2(0, *row_num as i32)), message));
+        }
+    }
+}
+
+/// Opens the editor on the provided maze file. A missing file starts a new maze of the provided size.
+pub fn run(path: &Path, width: usize, height: usize) -> io::Result<()> {
+    let mut editor = match fs::read_to_string(path) {
+        Ok(text) => Editor::from_text(&text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Editor::new(width, height),
+        Err(err) => return Err(err),
+    };
+
+    let _terminal = TerminalGuard::enter()?;
+    edit_loop(&mut stdout(), &mut editor, path)
+}
+
+/// Reads keys and redraws the editor until the user quits.
+fn edit_loop(stdout: &mut Stdout, editor: &mut Editor, path: &Path) -> io::Result<()> {
+    let mut saved_text = fs::read_to_string(path).unwrap_or_default();
+    let mut status = String::from("Ctrl+S saves, Ctrl+Q quits.");
+
+    loop {
+        draw(stdout, editor, path, &status)?;
+
+        let Event::Key(key) = read()? else { continue; };
+        if key.is_release() { continue; }
+
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        status.clear();
+        match key.code {
+            KeyCode::Up => editor.move_cursor(Direction::up()),
+            KeyCode::Down => editor.move_cursor(Direction::down()),
+            KeyCode::Left => editor.move_cursor(Direction::left()),
+            KeyCode::Right => editor.move_cursor(Direction::right()),
+
+            KeyCode::Char('s') if control => {
+                let text = editor.to_text();
+                fs::write(path, &text)?;
+                saved_text = text;
+                status = format!("Saved {}.", path.display());
+            },
+            KeyCode::Char('q') | KeyCode::Char('c') if control => {
+                if editor.to_text() == saved_text { return Ok(()); }
+                status = String::from("Unsaved changes! Press Ctrl+Q again to quit anyway.");
+                draw(stdout, editor, path, &status)?;
+                if let Event::Key(again) = read()?
+                    && again.modifiers.contains(KeyModifiers::CONTROL) && again.code == KeyCode::Char('q') {
+                    return Ok(());
+                }
+                status.clear();
+            },
+            KeyCode::Char('z') if control => editor.undo(),
+            KeyCode::Char('y') if control => editor.redo(),
+            KeyCode::Char('u') => editor.undo(),
+            KeyCode::Char('y') => editor.redo(),
+
+            KeyCode::Char('m') => editor.toggle_mirror(),
+            KeyCode::Char('p') => editor.toggle_painting(),
+            KeyCode::Backspace | KeyCode::Delete => editor.place(' '),
+            KeyCode::Char(tile) => editor.place(tile),
+            _ => {},
+        }
+    }
+}
+
+/// Draws the maze, the editor's help and the current validation problems.
+fn draw(stdout: &mut Stdout, editor: &Editor, path: &Path, status: &str) -> io::Result<()> {
+    execute!(stdout, cursor::Hide, Clear(ClearType::All))?;
+
+    let mut lines: Vec<String> = editor.rows.iter()
+        .map(|row| row.iter().map(|tile| if *tile == '#' { '█' } else { *tile }).collect())
+        .collect();
+
+    let errors = editor.diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count();
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let mut side = vec![
+        format!("Editing {}", path.display()),
+        format!("Cursor: ({}, {})", editor.cursor.0, editor.cursor.1),
+        format!("Mirror (m): {}  Paint (p): {}", on_off(editor.mirror), on_off(editor.painting)),
+        String::from("Arrows move. Place: # . * - ~ _ ^ v < > t(eleporter) space"),
+        String::from("Spawns: R B P I C   Undo: u/Ctrl+Z  Redo: y/Ctrl+Y"),
+        String::new(),
+        format!("{errors} error(s), {} warning(s)", editor.diagnostics.len() - errors),
+    ];
+    side.extend(editor.diagnostics.iter().take(8).map(|diagnostic| format!("  {diagnostic}")));
+    side.push(String::new());
+    side.push(status.to_string());
+
+    let width = editor.rows[0].len();
+    for (index, text) in side.into_iter().enumerate() {
+        match lines.get_mut(index) {
+            Some(line) => line.push_str(&format!("  {text}")),
+            None => lines.push(format!("{}  {text}", " ".repeat(width))),
+        }
+    }
+
+    for (row, line) in lines.iter().enumerate() {
+        execute!(stdout, cursor::MoveTo(0, row as u16), Print(line))?;
+    }
+
+    execute!(stdout, cursor::MoveTo(editor.cursor.0 as u16, editor.cursor.1 as u16), cursor::Show)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::parse_maze;
+    use crate::validate::has_errors;
+
+    /// Tests if placing tiles can be undone and redone.
+    #[test]
+    fn undo_redo() {
+        let mut editor = Editor::new(5, 5);
+        editor.place('.');
+        editor.move_cursor(Direction::right());
+        editor.place('*');
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some('*'));
+        editor.undo();
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some(' '));
+        editor.undo();
+        assert_eq!(editor.get_tile(Vector2(1, 1)), Some(' '));
+        editor.redo();
+        editor.redo();
+        assert_eq!(editor.get_tile(Vector2(1, 1)), Some('.'));
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some('*'));
+        editor.undo();
+        editor.place('-');
+        editor.redo(); // Nothing to redo after a new change.
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some('-'));
+    }
+
+    /// Tests if mirrored placing places on both halves but spawns only move.
+    #[test]
+    fn mirror() {
+        let mut editor = Editor::new(7, 3);
+        editor.toggle_mirror();
+        editor.place('#');
+        assert_eq!(editor.get_tile(Vector2(5, 1)), Some('#'));
+        editor.move_cursor(Direction::right());
+        editor.place('R');
+        editor.move_cursor(Direction::right());
+        editor.place('R');
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some(' '));
+        assert_eq!(editor.get_tile(Vector2(3, 1)), Some('R'));
+        assert_eq!(editor.get_tile(Vector2(4, 1)), Some(' '));
+
+        editor.move_cursor(Direction::left());
+        editor.place('<');
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some('<'));
+        assert_eq!(editor.get_tile(Vector2(4, 1)), Some('>'));
+    }
+
+    /// Tests if teleporters pair up, including across a mirror.
+    #[test]
+    fn teleporters_pair() {
+        let mut editor = Editor::new(7, 3);
+        editor.place('t');
+        editor.move_cursor(Direction::right());
+        editor.place('t');
+        editor.move_cursor(Direction::right());
+        editor.place('t');
+        assert_eq!(editor.get_tile(Vector2(1, 1)), Some('0'));
+        assert_eq!(editor.get_tile(Vector2(2, 1)), Some('0'));
+        assert_eq!(editor.get_tile(Vector2(3, 1)), Some('1'));
+
+        let mut editor = Editor::new(7, 3);
+        editor.toggle_mirror();
+        editor.place('t');
+        assert_eq!(editor.get_tile(Vector2(5, 1)), Some('0'));
+    }
+
+    /// Tests if painting places the last tile everywhere the cursor goes.
+    #[test]
+    fn painting() {
+        let mut editor = Editor::new(6, 3);
+        editor.place('.');
+        editor.toggle_painting();
+        editor.move_cursor(Direction::right());
+        editor.move_cursor(Direction::right());
+        editor.move_cursor(Direction::right());
+        assert_eq!(editor.to_text(), "######\n#....#\n######\n");
+
+        editor.toggle_painting();
+        editor.move_cursor(Direction::right());
+        editor.move_cursor(Direction::right()); // Blocked by the edge of the maze.
+        assert_eq!(editor.get_cursor(), Vector2(5, 1));
+        assert_eq!(editor.to_text(), "######\n#....#\n######\n");
+    }
+
+    /// Tests if a maze built in the editor validates once it's complete and loads in the game.
+    #[test]
+    fn build_and_load() {
+        let mut editor = Editor::new(9, 3);
+        assert!(has_errors(editor.get_diagnostics()));
+        for tile in ['R', 'B', 'P', 'I', 'C', '.', '.'] {
+            editor.place(tile);
+            editor.move_cursor(Direction::right());
+        }
+        assert!(!has_errors(editor.get_diagnostics()), "{:?}", editor.get_diagnostics());
+        let grid = parse_maze(&Editor::from_text(&editor.to_text()).to_text()).unwrap();
+        assert_eq!(grid.get_spawn(Character::Clyde), Vector2(5, 1));
+    }
+
+    /// Tests if loading keeps directives and fills out ragged rows with a warning.
+    #[test]
+    fn load_text() {
+        let editor = Editor::from_text("; comment\n@scatter inky 1 1\n███\n#.\n");
+        assert_eq!(editor.to_text(), "; comment\n@scatter inky 1 1\n###\n#.#\n");
+        let padded = editor.get_diagnostics().iter().find(|diagnostic| diagnostic.severity == Severity::Warning).unwrap();
+        assert_eq!(padded.position, Some(Vector2(0, 1)));
+        assert!(padded.message.starts_with("Row was 2 wide"));
+        assert!(Editor::from_text("###\n#.#\n").get_diagnostics().iter().all(|diagnostic| !diagnostic.message.starts_with("Row was")));
+    }
+
+    /// Tests if mazes a single tile thin can be edited without leaving the maze.
+    #[test]
+    fn thin_maze() {
+        let mut editor = Editor::from_text("R.BPIC\n");
+        assert_eq!(editor.get_cursor(), Vector2(1, 0));
+        editor.place('#');
+        assert_eq!(editor.to_text(), "R#BPIC\n");
+
+        let mut editor = Editor::from_text("#\n.\n.\n");
+        assert_eq!(editor.get_cursor(), Vector2(0, 1));
+        editor.toggle_mirror();
+        editor.place('*');
+        editor.move_cursor(Direction::right()); // Blocked by the edge of the maze.
+        editor.place('-');
+        assert_eq!(editor.to_text(), "#\n-\n.\n");
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::{self, Display};
+use std::io;
+
+use crate::grid::grid::GridPointError;
+
+/// Shorthand for results that fail with a `RucmanError`.
+pub type Result<T> = std::result::Result<T, RucmanError>;
+
+/// Everything that can go wrong in the library, so callers can recover instead of the game crashing.
+#[derive(Debug)]
+pub enum RucmanError {
+    Io(io::Error), // Reading or writing a file failed.
+    MazeParse(String), // A maze's text couldn't be turned into a grid.
+    InvalidConfig(String), // A level pack, setting or command line value doesn't make sense.
+    Grid(GridPointError), // The grid was used with a position it can't handle.
+    ReplayMismatch { frame: u128, expected: String, found: String }, // A replayed game went differently than it was recorded.
+}
+
+impl Display for RucmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RucmanError::Io(err) => write!(f, "{err}"),
+            RucmanError::MazeParse(message) | RucmanError::InvalidConfig(message) => write!(f, "{message}"),
+            RucmanError::Grid(err) => write!(f, "{err}"),
+            RucmanError::ReplayMismatch { frame, expected, found } => write!(f, "Replay went differently at frame {frame}: expected {expected} but found {found}"),
+        }
+    }
+}
+
+impl std::error::Error for RucmanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RucmanError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RucmanError {
+    fn from(err: io::Error) -> Self {
+        RucmanError::Io(err)
+    }
+}
+
+impl From<GridPointError> for RucmanError {
+    fn from(err: GridPointError) -> Self {
+        RucmanError::Grid(err)
+    }
+}
+
+/// Lets code that works with io errors, such as the executable, use `?` on library results.
+impl From<RucmanError> for io::Error {
+    fn from(err: RucmanError) -> Self {
+        match err {
+            RucmanError::Io(err) => err,
+            RucmanError::InvalidConfig(_) => io::Error::new(io::ErrorKind::InvalidInput, err.to_string()),
+            _ => io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if errors keep their messages when shown and when turned into io errors.
+    #[test]
+    fn messages() {
+        let err = RucmanError::MazeParse(String::from("Maze has no rows"));
+        assert_eq!(err.to_string(), "Maze has no rows");
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+
+        let err = RucmanError::from(io::Error::new(io::ErrorKind::NotFound, "missing.maze"));
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+
+        let err = RucmanError::ReplayMismatch { frame: 12, expected: String::from("(1, 1)"), found: String::from("(2, 1)") };
+        assert_eq!(err.to_string(), "Replay went differently at frame 12: expected (1, 1) but found (2, 1)");
+        assert_eq!(RucmanError::from(GridPointError::BadPosError).to_string(), "Position is outside the maze");
+    }
+}
@@ -0,0 +1,112 @@
+use tracing::{debug, info, trace};
+
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::character::{Character, GhostMode, Vulnerability};
+use crate::point::Vector2;
+
+/// Something that happened in the game, for the parts of the program that react to it without the engine
+/// knowing about them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    PelletEaten { position: Vector2 },
+    PowerPelletEaten { position: Vector2 },
+    GhostEaten { ghost: Character, points: u32 },
+    RucmanCaught { by: Character },
+    LevelCleared { level: u32 },
+    ExtraLife { lives: u8 }, // How many lives Rucman has with the new one.
+    ModeChanged { ghost: Character, mode: GhostMode, vulnerability: Vulnerability },
+}
+
+/// Reacts to game events.
+pub trait Subscriber: Send {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+/// Lets a subscriber be shared, so whoever subscribed it can still look at it.
+impl<T: Subscriber> Subscriber for Arc<Mutex<T>> {
+    fn notify(&mut self, event: &GameEvent) {
+        self.lock().unwrap_or_else(PoisonError::into_inner).notify(event);
+    }
+}
+
+/// Passes every event published to each subscriber, in the order they subscribed.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl EventBus {
+    /// Creates a bus without any subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a subscriber that's told about every event published from now on.
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// Tells every subscriber about the events, one event at a time.
+    pub fn publish(&mut self, events: &[GameEvent]) {
+        for event in events {
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.notify(event);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus").field("subscribers", &self.subscribers.len()).finish()
+    }
+}
+
+/// Writes events to the log. Every pellet is traced, mode changes are debug and everything else is info.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EventLogger;
+
+impl Subscriber for EventLogger {
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::PelletEaten { position } => trace!(?position, "Pellet eaten"),
+            GameEvent::PowerPelletEaten { position } => info!(?position, "Power pellet eaten"),
+            GameEvent::GhostEaten { ghost, points } => info!(%ghost, points, "Ghost eaten"),
+            GameEvent::RucmanCaught { by } => info!(%by, "Rucman caught"),
+            GameEvent::LevelCleared { level } => info!(level, "Level cleared"),
+            GameEvent::ExtraLife { lives } => info!(lives, "Extra life"),
+            GameEvent::ModeChanged { ghost, mode, vulnerability } => debug!(%ghost, ?mode, ?vulnerability, "Mode changed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts the events it's told about.
+    #[derive(Default)]
+    struct Counter(usize);
+
+    impl Subscriber for Counter {
+        fn notify(&mut self, _event: &GameEvent) {
+            self.0 += 1;
+        }
+    }
+
+    /// Tests if every subscriber hears every event, including shared ones that can be looked at afterwards.
+    #[test]
+    fn publishes_to_everyone() {
+        let shared = Arc::new(Mutex::new(Counter::default()));
+        let mut bus = EventBus::new();
+        bus.subscribe(shared.clone());
+        bus.subscribe(Counter::default());
+        bus.subscribe(EventLogger);
+
+        bus.publish(&[GameEvent::LevelCleared { level: 1 }, GameEvent::ExtraLife { lives: 4 }]);
+        bus.publish(&[]);
+        assert_eq!(shared.lock().unwrap().0, 2);
+    }
+}
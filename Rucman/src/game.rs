@@ -0,0 +1,621 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use tracing::info_span;
+
+use std::sync::Arc;
+
+use crate::character::{Character, CharacterData, ElroyStage, GhostMode, Vulnerability};
+use crate::direction::Direction;
+use crate::error::{Result, RucmanError};
+use crate::events::{EventBus, GameEvent, Subscriber};
+use crate::grid::grid::{Grid, GridPoint};
+use crate::level::LevelPack;
+use crate::managers::NumberManager;
+use crate::point::Vector2;
+
+/// Points for eating a vulnerable ghost.
+pub const GHOST_POINTS: u32 = 200;
+
+/// Denotes what happened during a single step of the game.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StepOutcome {
+    Continue,
+    Caught(Character), // Rucman ran into an invulnerable ghost and lost a life.
+    LevelComplete,
+}
+
+/// The game engine. Holds the whole state of a game and advances it one frame at a time without
+/// drawing anything, leaving input, rendering and timing to whoever runs it.
+///
+/// Games are cheap to clone, so bots can play out possible futures on copies. Clones share the level pack and the
+/// maze's distance tables, and carry on with the same random choices.
+#[derive(Debug, Clone)]
+pub struct Game {
+    pack: Arc<LevelPack>,
+    grid: Grid,
+    rucman: CharacterData,
+    ghosts: Vec<CharacterData>,
+    number_manager: NumberManager,
+    frames: u128,
+    rng: StdRng, // Every random choice in the game comes from here.
+    seed: u64,
+    god_mode: bool, // Invulnerable ghosts pass through Rucman instead of catching him.
+    won: bool, // Rucman cleared the last level of a pack that doesn't wrap.
+    events: Vec<GameEvent>, // What's happened since the last step started.
+    events_frame: u128, // The frame the events happened on.
+}
+
+impl Game {
+    /// Creates a game starting at the first level of the provided pack.
+    pub fn new(pack: LevelPack) -> Result<Self> {
+        Self::with_seed(pack, rand::random())
+    }
+
+    /// Creates a game whose random choices all come from the provided seed, so it plays out the same way every
+    /// time it gets the same input.
+    pub fn with_seed(pack: LevelPack, seed: u64) -> Result<Self> {
+        let first_level = pack.get_level(1).ok_or_else(|| RucmanError::InvalidConfig(String::from("Pack has no levels")))?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut grid = first_level.build_grid()?;
+        grid.shuffle_open_spaces(&mut rng);
+        let mut number_manager = NumberManager::new();
+        number_manager.apply_level_overrides(first_level.get_overrides());
+
+        let mut res = Game {
+            rucman: CharacterData::spawn(Character::Rucman, &grid),
+            ghosts: Vec::new(),
+            grid,
+            pack: Arc::new(pack),
+            number_manager,
+            frames: 0,
+            rng,
+            seed,
+            god_mode: false,
+            events: Vec::new(),
+            events_frame: 0,
+            won: false,
+        };
+        res.respawn();
+
+        Ok(res)
+    }
+
+    /// Borrow the level pack being played.
+    pub fn get_pack(&self) -> &LevelPack {
+        &self.pack
+    }
+
+    /// Borrow the current maze.
+    pub fn get_grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Borrow Rucman's character data.
+    pub fn get_rucman(&self) -> &CharacterData {
+        &self.rucman
+    }
+
+    /// Borrow the ghosts' character data.
+    pub fn get_ghosts(&self) -> &Vec<CharacterData> {
+        &self.ghosts
+    }
+
+    /// Borrow the score, lives, level and timers.
+    pub fn get_number_manager(&self) -> &NumberManager {
+        &self.number_manager
+    }
+
+    /// Mutably borrow the score, lives, level and timers, for tools that change them directly.
+    pub fn get_number_manager_mut(&mut self) -> &mut NumberManager {
+        &mut self.number_manager
+    }
+
+    /// Retrieves the seed the game's random choices come from.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns true if ghosts can't catch Rucman.
+    pub fn is_god_mode(&self) -> bool {
+        self.god_mode
+    }
+
+    /// Sets whether ghosts can catch Rucman.
+    pub fn set_god_mode(&mut self, god_mode: bool) {
+        self.god_mode = god_mode;
+    }
+
+    /// Borrow the events since the last step started, including any from loading a level after it.
+    pub fn get_events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Takes the events since the last step started, or since they were last taken, to publish them.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Publishes the events since the last step started, or since they were last taken, inside a span with the
+    /// frame they happened on. They're taken so they're only published once.
+    pub fn publish_events(&mut self, bus: &mut EventBus) {
+        let _frame = info_span!("frame", frame = self.events_frame).entered();
+        bus.publish(&self.take_events());
+    }
+
+    /// Retrieves how many frames have been played.
+    pub fn get_frames(&self) -> u128 {
+        self.frames
+    }
+
+    /// Returns true once Rucman is out of lives.
+    pub fn is_over(&self) -> bool {
+        self.number_manager.get_lives() == 0
+    }
+
+    /// Returns true once Rucman has cleared every level of a pack that doesn't wrap.
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    /// Points Rucman in the provided direction if he's able to move that way.
+    pub fn turn(&mut self, direction: Direction) {
+        self.rucman.set_direction_if_valid(direction, &self.grid);
+    }
+
+    /// Advances the game by a single frame, recording what happened in it as events.
+    /// Stops early if Rucman gets caught, leaving everyone where they were so it can be shown before `respawn`.
+    pub fn step(&mut self) -> StepOutcome {
+        let _frame = info_span!("frame", frame = self.frames).entered();
+        self.events.clear();
+        self.events_frame = self.frames;
+        let modes = self.ghost_modes();
+        let outcome = self.advance();
+        self.emit_mode_changes(&modes);
+
+        outcome
+    }
+
+    /// Plays out a frame for `step`.
+    fn advance(&mut self) -> StepOutcome {
+        // Move rucman (rucman's direction is controlled by turn.)
+        self.rucman.rucman_move(&self.grid);
+
+        //Eat pellets
+        let position = self.rucman.get_position();
+        match self.grid.eat(&position) {
+            Ok(GridPoint::Pellet) => self.emit(GameEvent::PelletEaten { position }),
+            Ok(GridPoint::PowerPellet) => {
+                for ghost in self.ghosts.iter_mut() {
+                    ghost.frighten();
+                }
+                self.number_manager.start_vulnerability_timer();
+                self.emit(GameEvent::PowerPelletEaten { position });
+            },
+            _ => {}, // Empty spaces and anything inedible, such as the teleporter Rucman arrived on.
+        }
+
+        // Check if rucman ran into a ghost.
+        if let Some(ghost) = self.check_collision() { return StepOutcome::Caught(ghost); }
+
+        // Cruise Elroy: Blinky speeds up and stops scattering as pellets run low.
+        let elroy = self.number_manager.get_elroy_stage(self.grid.pellets_left());
+        let rucman_position = self.rucman.get_position();
+        let rucman_direction = self.rucman.get_direction();
+
+        //Move Ghosts
+        for ghost in self.ghosts.iter_mut() {
+            match ghost.get_vulnerability() {
+                Vulnerability::Invulnerable => {
+                    let is_elroy = ghost.get_character() == Character::Blinky && elroy != ElroyStage::Inactive;
+                    if is_elroy && ghost.get_ghost_mode() == GhostMode::Scatter {
+                        ghost.set_chase_mode();
+                    }
+
+                    // Ghosts in tunnels only move on even frames, the same as vulnerable ghosts.
+                    let in_tunnel = self.grid.get_point(&ghost.get_position()) == Some(GridPoint::Tunnel);
+                    if in_tunnel && !self.frames.is_multiple_of(2) { continue; }
+
+                    ghost.ghost_move(&mut self.grid, rucman_position, rucman_direction);
+
+                    // Don't take the extra step if it would walk Blinky off of rucman.
+                    if is_elroy && elroy.is_extra_step(self.frames) && ghost.get_position() != rucman_position {
+                        ghost.ghost_move(&mut self.grid, rucman_position, rucman_direction);
+                    }
+                },
+                Vulnerability::Vulnerable => { // To make vulnerable ghosts slower, they only move on even frames.
+                    if self.frames.is_multiple_of(2) {
+                        ghost.ghost_move(&mut self.grid, rucman_position, rucman_direction);
+                    }
+                    if self.number_manager.is_vulnerability_over() {
+                        ghost.set_invulnerable();
+                    }
+                },
+                Vulnerability::Eaten => ghost.ghost_move(&mut self.grid, rucman_position, rucman_direction),
+            }
+        }
+
+        // Check if a ghost ran into rucman.
+        if let Some(ghost) = self.check_collision() { return StepOutcome::Caught(ghost); }
+
+        // Update time data.
+        self.number_manager.tick_vulernability_timer();
+        self.number_manager.tick_elroy_suspension();
+        if self.frames == u128::MAX { self.frames = 0; } //Probably would never happen. Essentially overflow anyway, but this is to define what to happen on overflow.
+        else { self.frames += 1; }
+
+        // Scatter ghosts on time.
+        if self.frames.is_multiple_of(self.number_manager.get_scatter_interval()) {
+            for ghost in self.ghosts.iter_mut() {
+                ghost.set_scatter_mode();
+            }
+        }
+
+        if self.grid.pellets_left() == 0 {
+            self.emit(GameEvent::LevelCleared { level: self.number_manager.get_level() });
+            return StepOutcome::LevelComplete;
+        }
+
+        StepOutcome::Continue
+    }
+
+    /// Resets all characters to their initial state.
+    pub fn respawn(&mut self) {
+        self.rucman = CharacterData::spawn(Character::Rucman, &self.grid);
+        self.ghosts = vec![
+            CharacterData::spawn(Character::Inky, &self.grid),
+            CharacterData::spawn(Character::Blinky, &self.grid),
+            CharacterData::spawn(Character::Pinky, &self.grid),
+            CharacterData::spawn(Character::Clyde, &self.grid),
+        ];
+    }
+
+    /// Levels up and loads the pack's next maze.
+    /// Returns false if the pack doesn't have another level, meaning Rucman won.
+    pub fn next_level(&mut self) -> Result<bool> {
+        let lives = self.number_manager.get_lives();
+        let current = self.pack.get_level(self.number_manager.get_level());
+        self.number_manager.level_up();
+        let Some(next) = self.pack.get_level(self.number_manager.get_level()) else {
+            self.won = true;
+            return Ok(false);
+        };
+
+        // Replaying the same maze, such as when the pack wraps around, only needs the pellets put back.
+        if current.is_some_and(|current| std::ptr::eq(current, next)) { self.grid.reset_pellets(); }
+        else {
+            self.grid = next.build_grid()?;
+            self.grid.shuffle_open_spaces(&mut self.rng);
+        }
+        self.number_manager.apply_level_overrides(next.get_overrides());
+        self.respawn();
+
+        // The bonus for clearing the level can earn a life.
+        let now = self.number_manager.get_lives();
+        if now > lives { self.emit(GameEvent::ExtraLife { lives: now }); }
+        Ok(true)
+    }
+
+    /// Jumps straight to the provided level with the timers it would have after clearing every level before it,
+    /// keeping the score and lives.
+    pub fn warp_to_level(&mut self, level: u32) -> Result<()> {
+        let target = self.pack.get_level(level).filter(|_| level > 0)
+            .ok_or_else(|| RucmanError::InvalidConfig(format!("{} doesn't have a level {level}", self.pack.get_name())))?;
+
+        self.number_manager.restart_levels();
+        let cycle = self.pack.level_count() as u32;
+        let mut cycle_timers = None;
+        let mut number = 1;
+        loop {
+            if number > 1 { self.number_manager.advance_level(); }
+            if let Some(passed) = self.pack.get_level(number) { self.number_manager.apply_level_overrides(passed.get_overrides()); }
+
+            // Once a trip through the pack ends with the same timers as the last one, every later trip does too.
+            if number.is_multiple_of(cycle) {
+                let timers = (self.number_manager.get_scatter_interval(), self.number_manager.get_vulnerability_length());
+                if cycle_timers == Some(timers) {
+                    let skipped = (level - number) / cycle * cycle;
+                    self.number_manager.skip_levels(skipped);
+                    number += skipped;
+                }
+                cycle_timers = Some(timers);
+            }
+
+            if number >= level { break; }
+            number += 1;
+        }
+
+        self.grid = target.build_grid()?;
+        self.grid.shuffle_open_spaces(&mut self.rng);
+        self.respawn();
+        Ok(())
+    }
+
+    /// Moves Rucman to the provided position, keeping the way he's facing. Fails if he couldn't stand there.
+    pub fn teleport(&mut self, pos: Vector2) -> Result<()> {
+        if !self.grid.is_valid_pos(&pos) || self.grid.get_point(&pos) == Some(GridPoint::Door) {
+            return Err(RucmanError::InvalidConfig(format!("Rucman can't stand at {},{}", pos.0, pos.1)));
+        }
+
+        self.rucman.set_position(pos);
+        Ok(())
+    }
+
+    /// Puts a ghost in the provided mode. Fails if there's no such ghost in the maze.
+    pub fn set_ghost_mode(&mut self, character: Character, mode: GhostMode) -> Result<()> {
+        let modes = self.ghost_modes();
+        let ghost = self.ghosts.iter_mut().find(|ghost| ghost.get_character() == character)
+            .ok_or_else(|| RucmanError::InvalidConfig(format!("{character} isn't a ghost in this maze")))?;
+
+        match mode {
+            GhostMode::Scatter => ghost.set_scatter_mode(),
+            GhostMode::Chase => ghost.set_chase_mode(),
+        }
+        self.emit_mode_changes(&modes);
+        Ok(())
+    }
+
+    /// Makes every ghost vulnerable for the provided number of frames, the same as a power pellet without the points.
+    pub fn frighten(&mut self, frames: u32) {
+        let modes = self.ghost_modes();
+        for ghost in self.ghosts.iter_mut() {
+            ghost.frighten();
+        }
+        self.number_manager.set_vulnerability_timer(frames);
+        self.emit_mode_changes(&modes);
+    }
+
+    /// Eats every pellet left, so the next step completes the level.
+    pub fn eat_all(&mut self) {
+        self.grid.eat_all();
+    }
+
+    /// Checks for collisions between rucman and the ghosts and handles the cases for vulnerable and invulnerable ghosts.
+    /// Eaten ghosts are sent home and pass through Rucman on the way. Returns a character if rucman collided with
+    /// an invulnerable ghost.
+    fn check_collision(&mut self) -> Option<Character> {
+        for index in 0..self.ghosts.len() {
+            let ghost = self.ghosts[index].get_character();
+            if self.ghosts[index].get_position() != self.rucman.get_position() { continue; }
+
+            match self.ghosts[index].get_vulnerability() {
+                Vulnerability::Vulnerable => {
+                    self.ghosts[index].set_eaten();
+                    self.emit(GameEvent::GhostEaten { ghost, points: GHOST_POINTS });
+                },
+                Vulnerability::Eaten => {},
+                Vulnerability::Invulnerable if self.god_mode => {},
+                Vulnerability::Invulnerable => {
+                    self.emit(GameEvent::RucmanCaught { by: ghost });
+                    return Some(ghost);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Records an event after the scorer has handled it, along with an extra life if scoring it earned one.
+    /// Everything else hears about events once they're published on a bus, but the scorer can't wait: the score
+    /// and lives have to be right by the end of the step for the extra life and game over it may cause, and bots
+    /// playing out futures on clones need them scored without a bus of their own.
+    fn emit(&mut self, event: GameEvent) {
+        let lives = self.number_manager.get_lives();
+        self.number_manager.notify(&event);
+        self.events.push(event);
+
+        let now = self.number_manager.get_lives();
+        if now > lives { self.emit(GameEvent::ExtraLife { lives: now }); }
+    }
+
+    /// Retrieves what mode every ghost is in, to tell which ones change.
+    fn ghost_modes(&self) -> Vec<(Character, GhostMode, Vulnerability)> {
+        self.ghosts.iter().map(|ghost| (ghost.get_character(), ghost.get_ghost_mode(), ghost.get_vulnerability())).collect()
+    }
+
+    /// Records a mode change for every ghost that's in a different mode than before.
+    fn emit_mode_changes(&mut self, before: &[(Character, GhostMode, Vulnerability)]) {
+        let changes: Vec<GameEvent> = self.ghost_modes().into_iter().zip(before)
+            .filter(|(now, before)| now.0 == before.0 && now != *before)
+            .map(|((ghost, mode, vulnerability), _)| GameEvent::ModeChanged { ghost, mode, vulnerability })
+            .collect();
+
+        for event in changes {
+            self.emit(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::grid::Mover;
+    use crate::level::LevelOverrides;
+
+    /// Tests if Rucman eats pellets and scores as he moves.
+    #[test]
+    fn step_eats_pellets() {
+        let mut game = Game::new(LevelPack::classic()).unwrap();
+        let pellets = game.get_grid().pellets_left();
+        assert_eq!(game.step(), StepOutcome::Continue);
+        assert_eq!(game.get_rucman().get_position(), Vector2(14, 20));
+        assert_eq!(game.get_grid().pellets_left(), pellets - 1);
+        assert_eq!(game.get_number_manager().get_score(), 5);
+        assert_eq!(game.get_frames(), 1);
+    }
+
+    /// Tests if steps record what happened in them as events, with the score following from them.
+    #[test]
+    fn step_events() {
+        let mut game = Game::with_seed(LevelPack::classic(), 2).unwrap();
+        game.step();
+        assert_eq!(game.get_events(), &[GameEvent::PelletEaten { position: Vector2(14, 20) }]);
+
+        let ghost = loop {
+            if let StepOutcome::Caught(ghost) = game.step() { break ghost; }
+        };
+        assert!(game.get_events().contains(&GameEvent::RucmanCaught { by: ghost }));
+        assert_eq!(game.get_number_manager().get_lives(), 2);
+
+        assert!(!game.take_events().is_empty());
+        assert!(game.get_events().is_empty());
+    }
+
+    /// Tests if Rucman goes through the tunnel during a step.
+    #[test]
+    fn step_through_tunnel() {
+        let mut game = Game::new(LevelPack::classic()).unwrap();
+        game.rucman.set_position(Vector2(1, 12));
+        game.rucman.set_direction(Direction::left());
+        game.ghosts.clear();
+        game.step();
+        assert_eq!(game.get_rucman().get_position(), Vector2(26, 12));
+        game.step();
+        assert_eq!(game.get_rucman().get_position(), Vector2(25, 12));
+    }
+
+    /// Tests if ghosts are slowed down in tunnels but Rucman isn't.
+    #[test]
+    fn tunnels_slow_ghosts() {
+        let text = "#########\n#R~~~~~B#\n#.#####.#\n#.PIC...#\n#########\n";
+        let mut game = Game::new(LevelPack::classic()).unwrap();
+        game.grid = crate::maze::parse_maze(text).unwrap();
+        game.number_manager.apply_level_overrides(&LevelOverrides { elroy_thresholds: Some((0, 0)), ..Default::default() });
+        game.respawn();
+        game.ghosts.retain(|ghost| ghost.get_character() == Character::Blinky);
+        game.ghosts[0].set_chase_mode();
+        game.rucman.set_direction(Direction::up());
+
+        game.step();
+        assert_eq!(game.ghosts[0].get_position(), Vector2(6, 1)); // Moved onto the tunnel on an even frame.
+        game.step();
+        assert_eq!(game.ghosts[0].get_position(), Vector2(6, 1));
+        game.step();
+        assert_eq!(game.ghosts[0].get_position(), Vector2(5, 1));
+
+        game.rucman.set_direction(Direction::right());
+        game.step();
+        assert_eq!(game.get_rucman().get_position(), Vector2(2, 1));
+    }
+
+    /// Tests if replaying the same maze puts every pellet back.
+    #[test]
+    fn next_level_resets_pellets() {
+        let mut game = Game::new(LevelPack::classic()).unwrap();
+        let pellets = game.get_grid().pellets_left();
+        game.step();
+        assert!(game.next_level().unwrap());
+        assert_eq!(game.get_grid().pellets_left(), pellets);
+        assert_eq!(game.get_grid().get_point(&Vector2(14, 20)), Some(GridPoint::Pellet));
+        assert_eq!(game.get_number_manager().get_level(), 2);
+    }
+
+    /// Tests if a clone plays on by itself, without changing the original, the same way the original would have.
+    #[test]
+    fn clones_are_independent() {
+        let mut game = Game::with_seed(LevelPack::classic(), 9).unwrap();
+        game.turn(Direction::right());
+        for _ in 0..10 {
+            game.step();
+        }
+
+        let mut future = game.clone();
+        future.turn(Direction::left());
+        for _ in 0..50 {
+            future.step();
+        }
+        assert_eq!(game.get_frames(), 10);
+        assert_ne!(future.get_grid().pellets_left(), game.get_grid().pellets_left());
+
+        let mut copy = game.clone();
+        for game in [&mut game, &mut copy] {
+            game.turn(Direction::right());
+            for _ in 0..200 {
+                if game.step() != StepOutcome::Continue { break; }
+            }
+        }
+        assert_eq!(game.get_rucman(), copy.get_rucman());
+        assert_eq!(game.get_ghosts(), copy.get_ghosts());
+    }
+
+    /// Tests if games with the same seed play out the same, including Clyde's wandering.
+    #[test]
+    fn seeded_games_match() {
+        let mut first = Game::with_seed(LevelPack::classic(), 7).unwrap();
+        let mut second = Game::with_seed(LevelPack::classic(), 7).unwrap();
+        for frame in 0..300 {
+            if frame % 10 == 0 {
+                first.turn(Direction::directions()[frame / 10 % 4]);
+                second.turn(Direction::directions()[frame / 10 % 4]);
+            }
+            assert_eq!(first.step(), second.step());
+            if first.get_rucman().get_position() != second.get_rucman().get_position() { panic!("Rucman split up on frame {frame}"); }
+            for (ghost, other) in first.get_ghosts().iter().zip(second.get_ghosts()) {
+                assert_eq!(ghost.get_position(), other.get_position(), "{} split up on frame {frame}", ghost.get_character());
+            }
+            if first.is_over() { break; }
+        }
+        assert_eq!(first.get_number_manager().get_score(), second.get_number_manager().get_score());
+    }
+
+    /// Tests if warping far ahead gives the same timers as clearing every level on the way, without taking forever.
+    #[test]
+    fn warp_to_far_levels() {
+        let pack = LevelPack::load(std::path::Path::new("packs/arcade.pack")).unwrap();
+        let mut game = Game::with_seed(pack.clone(), 2).unwrap();
+        for level in [1, 2, 3, 8, 101, 1000] {
+            let mut expected = NumberManager::new();
+            for number in 1..=level {
+                if number > 1 { expected.advance_level(); }
+                expected.apply_level_overrides(pack.get_level(number).unwrap().get_overrides());
+            }
+
+            game.warp_to_level(level).unwrap();
+            let number_manager = game.get_number_manager();
+            assert_eq!(number_manager.get_level(), level);
+            assert_eq!(number_manager.get_scatter_interval(), expected.get_scatter_interval());
+            assert_eq!(number_manager.get_vulnerability_length(), expected.get_vulnerability_length());
+            assert_eq!(number_manager.get_elroy_thresholds(), expected.get_elroy_thresholds());
+        }
+
+        game.warp_to_level(u32::MAX).unwrap();
+        assert_eq!(game.get_number_manager().get_level(), u32::MAX);
+        game.number_manager.level_up();
+        assert_eq!(game.get_number_manager().get_level(), u32::MAX);
+    }
+
+    /// Tests if an eaten ghost heads back through the ghost house door as eyes and comes back to life there.
+    #[test]
+    fn eaten_ghost_returns_home() {
+        let mut game = Game::with_seed(LevelPack::classic(), 3).unwrap();
+        game.frighten(1000);
+        let pinky = game.ghosts.iter().position(|ghost| ghost.get_character() == Character::Pinky).unwrap();
+        game.ghosts[pinky].set_position(Vector2(14, 20));
+        game.step();
+        assert!(game.get_events().contains(&GameEvent::GhostEaten { ghost: Character::Pinky, points: GHOST_POINTS }));
+        assert_eq!(game.ghosts[pinky].get_vulnerability(), Vulnerability::Eaten);
+        assert_eq!(game.ghosts[pinky].get_mover(), Mover::EatenGhost);
+
+        let mut frames = 0;
+        while game.ghosts[pinky].get_vulnerability() == Vulnerability::Eaten {
+            assert!(!matches!(game.step(), StepOutcome::Caught(_)));
+            frames += 1;
+        }
+        assert!(frames > 1);
+        assert_eq!(game.ghosts[pinky].get_position(), game.get_grid().get_spawn(Character::Pinky));
+        assert_eq!(game.ghosts[pinky].get_vulnerability(), Vulnerability::Invulnerable);
+    }
+
+    /// Tests if running into a ghost costs a life and respawning puts everyone back.
+    #[test]
+    fn caught_and_respawn() {
+        let mut game = Game::new(LevelPack::classic()).unwrap();
+        let blinky = game.ghosts.iter().position(|ghost| ghost.get_character() == Character::Blinky).unwrap();
+        game.ghosts[blinky].set_position(Vector2(14, 20));
+        assert_eq!(game.step(), StepOutcome::Caught(Character::Blinky));
+        assert_eq!(game.get_number_manager().get_lives(), 2);
+
+        game.respawn();
+        assert_eq!(game.get_rucman().get_position(), Vector2(13, 20));
+        assert_eq!(game.ghosts[blinky].get_position(), Vector2(13, 9));
+    }
+}
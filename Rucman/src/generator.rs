@@ -0,0 +1,338 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::character::Character;
+use crate::error::Result;
+use crate::grid::grid::Grid;
+use crate::point::Vector2;
+
+/// Settings for generated mazes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MazeConfig {
+    pub width: usize,
+    pub height: usize,
+    pub tunnels: usize,
+}
+
+impl Default for MazeConfig {
+    /// About the size of the built-in maze with a single tunnel.
+    fn default() -> Self {
+        MazeConfig { width: 27, height: 31, tunnels: 1 }
+    }
+}
+
+impl MazeConfig {
+    /// Smallest width and height that fit the ghost house and a corridor around it.
+    pub const MIN_SIZE: usize = 15;
+
+    /// Retrieves the width the maze is actually generated with. Corridors sit on odd columns and the maze
+    /// mirrors around a corridor, so widths are rounded down to one less than a multiple of 4.
+    pub fn generated_width(&self) -> usize {
+        let width = self.width.max(Self::MIN_SIZE);
+        width - (width + 1) % 4
+    }
+
+    /// Retrieves the height the maze is actually generated with. Corridors sit on odd rows, so heights are
+    /// rounded down to an odd number.
+    pub fn generated_height(&self) -> usize {
+        let height = self.height.max(Self::MIN_SIZE);
+        height - (height + 1) % 2
+    }
+}
+
+/// Generates a maze from the provided seed. The same seed and config always make the same maze.
+///
+/// Generated mazes mirror left to right, every corridor connects and there are no dead ends. A ghost house
+/// with a door on top sits in the middle surrounded by an empty corridor, a power pellet sits in each corner
+/// and each tunnel is a teleporter pair on opposite sides of a row.
+pub fn generate(config: &MazeConfig, seed: u64) -> Result<Grid> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut layout = Layout::new(config.generated_width(), config.generated_height());
+
+    layout.carve_spanning_tree(&mut rng);
+    layout.remove_dead_ends(&mut rng);
+    let tunnels = layout.choose_tunnels(config.tunnels, &mut rng);
+
+    layout.build(&tunnels)
+}
+
+/// The maze while it's being carved. Cells are the odd, odd positions and passages are the positions between them.
+struct Layout {
+    width: usize,
+    height: usize,
+    center: Vector2,
+    open: Vec<Vec<bool>>,
+}
+
+impl Layout {
+    /// Creates a layout with the ghost house and the corridor around it already carved.
+    fn new(width: usize, height: usize) -> Self {
+        let center = Vector2((width / 2) as i32, (height / 2) as i32 & !1); // Odd column, even row.
+        let mut layout = Layout { width, height, center, open: vec![vec![false; width]; height] };
+
+        for pos in layout.ring() {
+            layout.set_open(pos);
+        }
+        for row in center.1 - 1..=center.1 + 1 {
+            for col in center.0 - 2..=center.0 + 2 {
+                layout.set_open(Vector2(col, row));
+            }
+        }
+        layout.set_open(center + Vector2(0, -2)); // House door
+
+        layout
+    }
+
+    /// Retrieves every position of the corridor around the ghost house.
+    fn ring(&self) -> Vec<Vector2> {
+        let Vector2(cx, cy) = self.center;
+        let mut res = Vec::new();
+        for row in cy - 3..=cy + 3 {
+            for col in cx - 4..=cx + 4 {
+                if row == cy - 3 || row == cy + 3 || col == cx - 4 || col == cx + 4 {
+                    res.push(Vector2(col, row));
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns true if the position is inside the corridor around the ghost house.
+    fn in_house_area(&self, pos: Vector2) -> bool {
+        let Vector2(cx, cy) = self.center;
+        (cx - 4..=cx + 4).contains(&pos.0) && (cy - 3..=cy + 3).contains(&pos.1)
+    }
+
+    /// Returns true if the position is a cell that corridors are carved between.
+    fn is_cell(&self, pos: Vector2) -> bool {
+        let Vector2(cx, cy) = self.center;
+        let inside_house = (cx - 3..=cx + 3).contains(&pos.0) && (cy - 2..=cy + 2).contains(&pos.1);
+        pos.0 % 2 == 1 && pos.1 % 2 == 1
+            && pos.0 > 0 && pos.1 > 0
+            && pos.0 < self.width as i32 - 1 && pos.1 < self.height as i32 - 1
+            && !inside_house
+    }
+
+    /// Retrieves the cells two steps away from the provided cell.
+    fn neighbour_cells(&self, cell: Vector2) -> Vec<Vector2> {
+        [Vector2(0, -2), Vector2(0, 2), Vector2(-2, 0), Vector2(2, 0)].into_iter()
+            .map(|offset| cell + offset)
+            .filter(|next| self.is_cell(*next))
+            .collect()
+    }
+
+    /// Retrieves the position mirrored across the center column.
+    fn mirror(&self, pos: Vector2) -> Vector2 {
+        Vector2(self.width as i32 - 1 - pos.0, pos.1)
+    }
+
+    fn is_open(&self, pos: Vector2) -> bool {
+        self.open[pos.1 as usize][pos.0 as usize]
+    }
+
+    fn set_open(&mut self, pos: Vector2) {
+        self.open[pos.1 as usize][pos.0 as usize] = true;
+    }
+
+    /// Opens the passage between two neighbouring cells on both halves of the maze.
+    fn connect(&mut self, first: Vector2, second: Vector2) {
+        let passage = Vector2((first.0 + second.0) / 2, (first.1 + second.1) / 2);
+        for pos in [first, second, passage] {
+            self.set_open(pos);
+            self.set_open(self.mirror(pos));
+        }
+    }
+
+    /// Connects every cell on the left half (and center column) with a random spanning tree using Kruskal's
+    /// algorithm. The corridor around the ghost house is already carved so it starts as a single set.
+    fn carve_spanning_tree(&mut self, rng: &mut StdRng) {
+        let cells: Vec<Vector2> = (0..self.height as i32)
+            .flat_map(|row| (0..=self.center.0).map(move |col| Vector2(col, row)))
+            .filter(|pos| self.is_cell(*pos))
+            .collect();
+        let index_of = |pos: Vector2| cells.iter().position(|cell| *cell == pos);
+
+        let mut sets: Vec<usize> = (0..cells.len()).collect();
+        let mut edges = Vec::new();
+        for (index, cell) in cells.iter().enumerate() {
+            for next in [*cell + Vector2(2, 0), *cell + Vector2(0, 2)] {
+                let Some(next_index) = index_of(next) else { continue; };
+                let passage = Vector2((cell.0 + next.0) / 2, (cell.1 + next.1) / 2);
+                if self.is_open(passage) {
+                    union(&mut sets, index, next_index); // Already carved around the ghost house.
+                }
+                else {
+                    edges.push((index, next_index));
+                }
+            }
+        }
+
+        edges.shuffle(rng);
+        for (first, second) in edges {
+            if find(&mut sets, first) != find(&mut sets, second) {
+                union(&mut sets, first, second);
+                self.connect(cells[first], cells[second]);
+            }
+        }
+    }
+
+    /// Counts the open passages leading out of a cell.
+    fn exits(&self, cell: Vector2) -> usize {
+        self.neighbour_cells(cell).into_iter()
+            .filter(|next| self.is_open(Vector2((cell.0 + next.0) / 2, (cell.1 + next.1) / 2)))
+            .count()
+    }
+
+    /// Opens an extra passage out of every cell that only has one, keeping the maze mirrored.
+    fn remove_dead_ends(&mut self, rng: &mut StdRng) {
+        for row in 0..self.height as i32 {
+            for col in 0..=self.center.0 {
+                let cell = Vector2(col, row);
+                if !self.is_cell(cell) || self.exits(cell) > 1 { continue; }
+
+                let closed: Vec<Vector2> = self.neighbour_cells(cell).into_iter()
+                    .filter(|next| !self.is_open(Vector2((cell.0 + next.0) / 2, (cell.1 + next.1) / 2)))
+                    .collect();
+                if let Some(next) = closed.choose(rng) {
+                    self.connect(cell, *next);
+                }
+            }
+        }
+    }
+
+    /// Picks up to the provided number of rows for tunnels, away from the ghost house and the corners.
+    fn choose_tunnels(&self, count: usize, rng: &mut StdRng) -> Vec<i32> {
+        let rows: Vec<i32> = (3..self.height as i32 - 3).step_by(2)
+            .filter(|row| !(self.center.1 - 3..=self.center.1 + 3).contains(row))
+            .collect();
+        let mut res: Vec<i32> = rows.choose_multiple(rng, count).copied().collect();
+        res.sort();
+        res
+    }
+
+    /// Converts the layout into a grid with pellets, power pellets, spawns, scatter positions and tunnels.
+    fn build(&self, tunnels: &[i32]) -> Result<Grid> {
+        let Vector2(cx, cy) = self.center;
+        let right = self.width as i32 - 2;
+        let bottom = self.height as i32 - 2;
+        let rucman_spawn = Vector2(cx, cy + 5);
+        let door = Vector2(cx, cy - 2);
+        let corners = [Vector2(1, 1), Vector2(right, 1), Vector2(1, bottom), Vector2(right, bottom)];
+
+        let mut maze = Vec::new();
+        for row in 0..self.height as i32 {
+            let mut row_collection = Vec::new();
+            for col in 0..self.width as i32 {
+                let pos = Vector2(col, row);
+                let is_tunnel = tunnels.contains(&row) && (col == 0 || col == right + 1);
+                let tile = if is_tunnel { ' ' }
+                    else if !self.is_open(pos) { '█' }
+                    else if corners.contains(&pos) { '*' }
+                    else if pos == door { '-' }
+                    else if self.in_house_area(pos) || pos == rucman_spawn { ' ' }
+                    else { '.' };
+                row_collection.push(tile);
+            }
+            maze.push(row_collection);
+        }
+
+        let mut grid = Grid::try_from_rows(maze)?;
+        for row in tunnels {
+            grid.add_teleporter_pair(Vector2(0, *row), Vector2(right + 1, *row))?;
+        }
+
+        grid.set_spawn(Character::Rucman, rucman_spawn);
+        grid.set_spawn(Character::Blinky, Vector2(cx, cy - 3));
+        grid.set_spawn(Character::Pinky, Vector2(cx, cy));
+        grid.set_spawn(Character::Inky, Vector2(cx - 2, cy));
+        grid.set_spawn(Character::Clyde, Vector2(cx + 2, cy));
+
+        grid.set_scatter_position(Character::Blinky, Vector2(right, 1));
+        grid.set_scatter_position(Character::Pinky, Vector2(1, 1));
+        grid.set_scatter_position(Character::Inky, Vector2(right, bottom));
+        grid.set_scatter_position(Character::Clyde, Vector2(1, bottom));
+
+        Ok(grid)
+    }
+}
+
+/// Finds the representative of the set the provided index belongs to.
+fn find(sets: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while sets[root] != root { root = sets[root]; }
+    sets[index] = root;
+    root
+}
+
+/// Merges the sets of the two provided indexes.
+fn union(sets: &mut [usize], first: usize, second: usize) {
+    let first = find(sets, first);
+    let second = find(sets, second);
+    sets[first] = second;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::grid::GridPoint;
+    use crate::maze;
+    use crate::validate::validate_grid;
+    use proptest::prelude::*;
+
+    /// Tests if the same seed makes the same maze and different seeds usually don't.
+    #[test]
+    fn seeded() {
+        let config = MazeConfig::default();
+        assert_eq!(generate(&config, 7).unwrap().get_maze(), generate(&config, 7).unwrap().get_maze());
+        assert_ne!(generate(&config, 7).unwrap().get_maze(), generate(&config, 8).unwrap().get_maze());
+    }
+
+    /// Tests if sizes are rounded to ones the generator can mirror.
+    #[test]
+    fn generated_sizes() {
+        let size = |width, height| {
+            let config = MazeConfig { width, height, tunnels: 1 };
+            (config.generated_width(), config.generated_height())
+        };
+        assert_eq!(size(27, 31), (27, 31));
+        assert_eq!(size(30, 30), (27, 29));
+        assert_eq!(size(0, 0), (15, 15));
+    }
+
+    proptest! {
+        /// Tests if every generated maze is valid, mirrored, free of dead ends and has four power pellets.
+        #[test]
+        fn generated_mazes_are_valid(seed in any::<u64>(), width in 0usize..45, height in 0usize..45, tunnels in 0usize..4) {
+            let config = MazeConfig { width, height, tunnels };
+            let grid = generate(&config, seed).unwrap();
+            prop_assert_eq!(grid.get_width(), config.generated_width());
+            prop_assert_eq!(grid.get_height(), config.generated_height());
+
+            let diagnostics = validate_grid(&grid);
+            prop_assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+            for row in grid.get_maze() {
+                let mirrored: Vec<bool> = row.iter().rev().map(|point| *point == GridPoint::Wall).collect();
+                let walls: Vec<bool> = row.iter().map(|point| *point == GridPoint::Wall).collect();
+                prop_assert_eq!(walls, mirrored);
+            }
+
+            let power_pellets = grid.get_maze().iter().flatten().filter(|point| **point == GridPoint::PowerPellet).count();
+            prop_assert_eq!(power_pellets, 4);
+        }
+
+        /// Tests if generated mazes survive being written to and read from the maze file format.
+        #[test]
+        fn generated_mazes_round_trip(seed in any::<u64>(), tunnels in 0usize..4) {
+            let grid = generate(&MazeConfig { tunnels, ..MazeConfig::default() }, seed).unwrap();
+            let parsed = maze::parse_maze(&maze::to_maze_text(&grid)).unwrap();
+            prop_assert_eq!(parsed.get_maze(), grid.get_maze());
+            for character in Character::characters() {
+                prop_assert_eq!(parsed.get_spawn(character), grid.get_spawn(character));
+                if character != Character::Rucman {
+                    prop_assert_eq!(parsed.get_scatter_position(character), grid.get_scatter_position(character));
+                }
+            }
+        }
+    }
+}
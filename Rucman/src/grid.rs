@@ -1,18 +1,45 @@
 // This was my first external module, so I was still figuring out the system :)
 pub mod grid {
-    use std::collections::VecDeque;
+    use std::collections::{HashMap, VecDeque};
+    use std::fmt::{self, Display};
+    use std::sync::{Arc, Mutex, PoisonError};
 
     use rand::prelude::*;
+    use crate::character::{Character, CharacterData};
+    use crate::direction::Direction;
+    use crate::distance::DistanceTable;
+    use crate::error::RucmanError;
     use crate::point::Vector2;
 
     /// Represents an entity that is a part of the grid.
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum GridPoint {
         Pellet,
         PowerPellet,
         Wall,
         Empty,
-        Teleporter(Vector2),
+        Teleporter(Portal),
+        Door, // The ghost house door. Only ghosts can go through.
+        Tunnel, // Slows ghosts down.
+        NoUp, // Ghosts can't move up from here.
+        OneWay(Direction), // Can only be crossed in its direction.
+    }
+
+    /// Denotes who is moving, since some tiles only let certain characters through.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Mover {
+        Rucman,
+        Ghost,
+        EatenGhost, // A ghost heading home after being eaten. Ignores no-up zones.
+    }
+
+    /// Where a teleporter sends whoever steps onto it. Movers keep their direction unless the portal has an exit direction.
+    /// Portals from a maze file's `@portal` directives keep their name so it can be written back out.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Portal {
+        pub destination: Vector2,
+        pub exit: Option<Direction>,
+        pub name: Option<Arc<str>>,
     }
 
     /// Represents errors when accessing the grid.
@@ -22,37 +49,124 @@ pub mod grid {
         BadPosError,
     }
 
+    impl Display for GridPointError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                GridPointError::InconsumableError(point) => write!(f, "{point:?} can't be eaten"),
+                GridPointError::BadPosError => write!(f, "Position is outside the maze"),
+            }
+        }
+    }
+
     impl From<GridPoint> for char {
         fn from(input: GridPoint) -> char {
             match input {
                 GridPoint::Pellet => '.',
                 GridPoint::PowerPellet => '*',
                 GridPoint::Wall => '█',
+                GridPoint::Door => '-',
+                GridPoint::OneWay(Direction::Up(_)) => '^',
+                GridPoint::OneWay(Direction::Down(_)) => 'v',
+                GridPoint::OneWay(Direction::Left(_)) => '<',
+                GridPoint::OneWay(Direction::Right(_)) => '>',
                 _ => ' ',
             }
         }
     }
 
+    impl GridPoint {
+        /// Retrieves the grid point a tile character stands for, if it's a tile at all.
+        pub fn from_tile(tile: char) -> Option<GridPoint> {
+            match tile {
+                '.' => Some(GridPoint::Pellet),
+                '*' => Some(GridPoint::PowerPellet),
+                '█' | '#' => Some(GridPoint::Wall),
+                ' ' => Some(GridPoint::Empty),
+                '-' => Some(GridPoint::Door),
+                '~' => Some(GridPoint::Tunnel),
+                '_' => Some(GridPoint::NoUp),
+                '^' => Some(GridPoint::OneWay(Direction::up())),
+                'v' => Some(GridPoint::OneWay(Direction::down())),
+                '<' => Some(GridPoint::OneWay(Direction::left())),
+                '>' => Some(GridPoint::OneWay(Direction::right())),
+                _ => None,
+            }
+        }
+    }
+
+    /// Panics on characters that aren't tiles. Use `GridPoint::from_tile` to handle them instead.
     impl From<char> for GridPoint {
         fn from(input: char) -> GridPoint {
-            match input {
-                '.' => GridPoint::Pellet,
-                '*' => GridPoint::PowerPellet,
-                '█' => GridPoint::Wall,
-                ' ' => GridPoint::Empty,
-                _ => panic!("Cannot convert {} to a grid point", input),
-            }
+            GridPoint::from_tile(input).unwrap_or_else(|| panic!("Cannot convert {} to a grid point", input))
         }
     }
 
-    /// Stores the grid and its meta data.
-    #[derive(Debug)]
-    pub struct Grid {
-        maze: Vec<Vec<GridPoint>>,
-        open_spaces: VecDeque<Vector2>,
+    /// Identifies a single cell of a grid. Cells are numbered row by row from the top left.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct CellId(usize);
+
+    impl CellId {
+        /// Retrieves the cell's index into the grid's storage.
+        pub fn index(self) -> usize {
+            self.0
+        }
+    }
+
+    /// A fixed size set of cells, one bit each.
+    #[derive(Debug, Clone, PartialEq)]
+    struct CellSet {
+        words: Vec<u64>,
+    }
+
+    impl CellSet {
+        /// Creates an empty set that can hold the provided number of cells.
+        fn new(cells: usize) -> Self {
+            CellSet { words: vec![0; cells.div_ceil(64)] }
+        }
+
+        /// Checks if the cell is in the set.
+        fn contains(&self, cell: CellId) -> bool {
+            self.words[cell.0 / 64] & (1 << (cell.0 % 64)) != 0
+        }
+
+        /// Adds the cell to the set.
+        fn insert(&mut self, cell: CellId) {
+            self.words[cell.0 / 64] |= 1 << (cell.0 % 64);
+        }
+
+        /// Takes the cell out of the set. Returns true if it was in the set.
+        fn remove(&mut self, cell: CellId) -> bool {
+            let had = self.contains(cell);
+            self.words[cell.0 / 64] &= !(1 << (cell.0 % 64));
+            had
+        }
+
+        /// Counts the cells in the set.
+        fn len(&self) -> u32 {
+            self.words.iter().map(|word| word.count_ones()).sum()
+        }
+    }
+
+    /// The parts of a maze that don't change while it's being played.
+    #[derive(Debug, Clone)]
+    struct Layout {
+        cells: Vec<GridPoint>, // Every tile of the maze, row by row. Pellets stay here after being eaten; `pellets` says which are left.
+        starting_pellets: CellSet, // The cells that had a pellet when the maze was built, for resetting.
+        ragged_rows: Vec<(usize, usize)>, // Rows that weren't as wide as the first when the grid was built, with their widths.
         width: usize,
         height: usize,
+        spawns: HashMap<Character, Vector2>,
+        scatter_positions: HashMap<Character, Vector2>,
+    }
+
+    /// Stores the grid and its meta data. Clones share the layout and only copy what changes during play.
+    #[derive(Debug, Clone)]
+    pub struct Grid {
+        layout: Arc<Layout>, // Copied the first time a grid sharing it is changed.
+        pellets: CellSet, // The cells that still have a pellet or power pellet.
+        open_spaces: VecDeque<Vector2>,
         pellets_left: u32,
+        distances: Arc<Mutex<HashMap<Mover, DistanceTable>>>, // Built the first time each mover needs one. Shared by clones, since they have the same ways through the maze.
     }
 
     impl Grid {
@@ -91,132 +205,312 @@ pub mod grid {
             ];
 
             let mut res = Self::from(maze);
-            res.maze[12][0] = GridPoint::Teleporter(Vector2(26, 12));
-            res.maze[12][26] = GridPoint::Teleporter(Vector2(0, 12));
+            res.add_teleporter_pair(Vector2(0, 12), Vector2(26, 12)).unwrap(); // Both ends are inside the built-in maze.
 
             res
         }
 
-        /// Borrow the maze from grid.
-        pub fn get_maze(&self) -> &Vec<Vec<GridPoint>> {
-            &self.maze
+        /// Parses the text of a maze file into a grid. See `maze::parse_maze` for the format.
+        pub fn try_from_str(text: &str) -> Result<Self, RucmanError> {
+            crate::maze::parse_maze(text)
+        }
+
+        /// Builds a grid from rows of tile characters, failing instead of panicking if there are no rows or a
+        /// character isn't a tile. Ragged rows are allowed, the same as with `Grid::from`.
+        pub fn try_from_rows(rows: Vec<Vec<char>>) -> Result<Self, RucmanError> {
+            if rows.first().is_none_or(|row| row.is_empty()) { return Err(RucmanError::MazeParse(String::from("Maze has no rows"))); }
+
+            for (row_num, row) in rows.iter().enumerate() {
+                if let Some(col_num) = row.iter().position(|tile| GridPoint::from_tile(*tile).is_none()) {
+                    return Err(RucmanError::MazeParse(format!("Unknown tile '{}' at column {col_num}, row {row_num}", row[col_num])));
+                }
+            }
+
+            Ok(Self::from(rows))
+        }
+
+        /// Links two positions with teleporters that lead to each other.
+        pub fn add_teleporter_pair(&mut self, first: Vector2, second: Vector2) -> Result<(), GridPointError> {
+            if self.cell_id(&first).is_none() || self.cell_id(&second).is_none() { return Err(GridPointError::BadPosError); }
+
+            self.add_portal(first, Portal { destination: second, exit: None, name: None })?;
+            self.add_portal(second, Portal { destination: first, exit: None, name: None })
+        }
+
+        /// Places a one-way teleporter at the provided position, replacing whatever was there.
+        pub fn add_portal(&mut self, entrance: Vector2, portal: Portal) -> Result<(), GridPointError> {
+            let cell = self.cell_id(&entrance).ok_or(GridPointError::BadPosError)?;
+            if self.pellets.remove(cell) { self.pellets_left -= 1; }
+            let layout = Arc::make_mut(&mut self.layout);
+            layout.starting_pellets.remove(cell);
+            layout.cells[cell.0] = GridPoint::Teleporter(portal);
+            self.open_spaces.retain(|pos| *pos != entrance);
+            self.distances = Arc::default(); // The ways through the maze changed, so this grid stops sharing the old tables.
+            Ok(())
+        }
+
+        /// Looks something up in the distance table of the maze for the provided mover, building the table if it
+        /// hasn't been yet.
+        pub fn distances<T>(&self, mover: Mover, lookup: impl FnOnce(&mut DistanceTable) -> T) -> T {
+            let mut distances = self.distances.lock().unwrap_or_else(PoisonError::into_inner);
+            let table = distances.entry(mover).or_insert_with(|| DistanceTable::new(self, mover));
+            lookup(table)
+        }
+
+        /// Retrieves where a mover stepping onto the provided position ends up and the direction it leaves in.
+        /// Positions without a teleporter are returned as they are.
+        pub fn follow_portal(&self, pos: Vector2, direction: Direction) -> (Vector2, Direction) {
+            match self.get_point(&pos) {
+                Some(GridPoint::Teleporter(portal)) => (portal.destination, portal.exit.unwrap_or(direction)),
+                _ => (pos, direction),
+            }
+        }
+
+        /// Retrieves where the provided character starts.
+        pub fn get_spawn(&self, character: Character) -> Vector2 {
+            self.layout.spawns[&character]
+        }
+
+        /// Sets where the provided character starts.
+        pub fn set_spawn(&mut self, character: Character, position: Vector2) {
+            Arc::make_mut(&mut self.layout).spawns.insert(character, position);
+        }
+
+        /// Retrieves where the provided character goes to during scatter mode.
+        pub fn get_scatter_position(&self, character: Character) -> Vector2 {
+            self.layout.scatter_positions[&character]
+        }
+
+        /// Sets where the provided character goes to during scatter mode.
+        pub fn set_scatter_position(&mut self, character: Character, position: Vector2) {
+            Arc::make_mut(&mut self.layout).scatter_positions.insert(character, position);
+        }
+
+        /// Finds the open space (not a wall or teleporter) closest to the provided point.
+        /// Ties go to the topmost, then leftmost space.
+        pub fn nearest_open_pos(&self, target: Vector2) -> Option<Vector2> {
+            let mut nearest: Option<Vector2> = None;
+            for (index, point) in self.layout.cells.iter().enumerate() {
+                let pos = self.cell_pos(CellId(index));
+                if !matches!(point, GridPoint::Pellet | GridPoint::PowerPellet | GridPoint::Empty) { continue; }
+
+                let closer = match nearest {
+                    Some(best) => Vector2::side_distance(pos, target) < Vector2::side_distance(best, target),
+                    None => true,
+                };
+                if closer { nearest = Some(pos); }
+            }
+
+            nearest
+        }
+
+        /// Retrieves the id of the cell at the provided position if it's inside the maze.
+        pub fn cell_id(&self, pos: &Vector2) -> Option<CellId> {
+            if pos.0 < 0 || pos.1 < 0 || pos.0 as usize >= self.layout.width || pos.1 as usize >= self.layout.height { return None; }
+            Some(CellId(pos.1 as usize * self.layout.width + pos.0 as usize))
+        }
+
+        /// Retrieves the position of the provided cell.
+        pub fn cell_pos(&self, cell: CellId) -> Vector2 {
+            Vector2((cell.0 % self.layout.width) as i32, (cell.0 / self.layout.width) as i32)
+        }
+
+        /// Retrieves the grid point of the provided cell. Eaten pellets are empty.
+        pub fn get_cell(&self, cell: CellId) -> GridPoint {
+            match &self.layout.cells[cell.0] {
+                GridPoint::Pellet | GridPoint::PowerPellet if !self.pellets.contains(cell) => GridPoint::Empty,
+                point => point.clone(),
+            }
+        }
+
+        /// Retrieves the grid point at the provided position if it's inside the maze.
+        pub fn get_point(&self, pos: &Vector2) -> Option<GridPoint> {
+            self.cell_id(pos).map(|cell| self.get_cell(cell))
+        }
+
+        /// Checks if there's an uneaten pellet or power pellet at the provided position.
+        pub fn has_pellet(&self, pos: &Vector2) -> bool {
+            self.cell_id(pos).is_some_and(|cell| self.pellets.contains(cell))
+        }
+
+        /// Copies the maze out of the grid, row by row.
+        pub fn get_maze(&self) -> Vec<Vec<GridPoint>> {
+            (0..self.layout.cells.len()).map(|index| self.get_cell(CellId(index))).collect::<Vec<_>>()
+                .chunks(self.layout.width).map(|row| row.to_vec()).collect()
+        }
+
+        /// Retrieves the rows that didn't match the first row's width when the grid was built, along with the
+        /// width they had. They were filled out with walls or cut short to fit.
+        pub fn get_ragged_rows(&self) -> &[(usize, usize)] {
+            &self.layout.ragged_rows
         }
 
         /// Retrieves the full width of the maze.
         pub fn get_width(&self) -> usize {
-            self.width
+            self.layout.width
         }
 
         /// Retrieves the full height of the maze.
         pub fn get_height(&self) -> usize {
-            self.height
+            self.layout.height
         }
 
-        /// Retrieves a random valid position of the maze.
-        pub fn get_random_position(&mut self) -> Vector2 {
-            let dest = self.open_spaces.pop_front().unwrap();
+        /// "Randomly" shuffles the open spaces handed out by `get_random_position`. The same rng state always gives
+        /// the same order, so seeded games play out the same way.
+        pub fn shuffle_open_spaces(&mut self, rng: &mut impl Rng) {
+            let mut spaces: Vec<Vector2> = self.open_spaces.drain(..).collect();
+            spaces.sort_by_key(|pos| (pos.1, pos.0));
+
+            // A pattern is generated where higher and leftmost spaces are more central to the vec
+            // and that lower and rightmost spaces are more outter to the vec, but it serves its
+            // purpose for making a "random" position.
+            for pos in spaces {
+                if rng.random::<u32>() % 2 == 0 { self.open_spaces.push_back(pos); }
+                else { self.open_spaces.push_front(pos); }
+            }
+        }
+
+        /// Retrieves a random valid position of the maze. Returns None if the maze has no open spaces.
+        pub fn get_random_position(&mut self) -> Option<Vector2> {
+            let dest = self.open_spaces.pop_front()?;
             self.open_spaces.push_back(dest);
-            dest
+            Some(dest)
         }
 
         /// Check to see if provided point is a valid position for an entity to be on.
         pub fn is_valid_pos(&self, pos: &Vector2) -> bool {
-            let col: i32 = pos.0;
-            let row: i32 = pos.1;
-            
-            if col < 0 || row < 0 { return false; }
-
-            let col: usize = col.try_into().unwrap();
-            let row: usize = row.try_into().unwrap();
+            match self.cell_id(pos) {
+                Some(cell) => self.layout.cells[cell.0] != GridPoint::Wall,
+                None => false,
+            }
+        }
 
-            if col >= self.width || row >= self.height { return false; }
+        /// Check to see if the mover is allowed to step from the provided position in the provided direction.
+        /// Doors only let ghosts through, ghosts that haven't been eaten can't move up out of no-up zones and
+        /// one-way tiles can only be entered or left going their way.
+        pub fn can_move(&self, from: &Vector2, direction: Direction, mover: Mover) -> bool {
+            let to = from.forward(direction);
+            if !self.is_valid_pos(&to) { return false; }
+
+            match self.get_point(from) {
+                Some(GridPoint::NoUp) if mover == Mover::Ghost && direction == Direction::up() => return false,
+                Some(GridPoint::OneWay(way)) if direction == way.opposite() => return false,
+                _ => {},
+            }
 
-            match self.maze[row][col] {
-                GridPoint::Wall => false,
+            match self.get_point(&to) {
+                Some(GridPoint::Door) => mover != Mover::Rucman,
+                Some(GridPoint::OneWay(way)) => direction == way,
                 _ => true,
             }
         }
 
-        /// Retrieves the GridPoint stored at the provided point and replaces it with empty.
-        pub fn eat(&mut self, pos: &Vector2) -> Result<GridPoint, GridPointError> {            
-            let col: i32 = pos.0;
-            let row: i32 = pos.1;
-            
-            if col < 0 || row < 0 { return Err(GridPointError::BadPosError); }
-
-            let col: usize = col.try_into().unwrap();
-            let row: usize = row.try_into().unwrap();
-
-            if col >= self.width || row >= self.height { return Err(GridPointError::BadPosError); }
-
-            let res = {
-                match self.maze[row][col] {
-                    GridPoint::Pellet | GridPoint::PowerPellet  => {
-                            self.pellets_left -= 1;
-                            Ok(self.maze[row][col]) 
-                        },
-                    GridPoint::Empty => Ok(GridPoint::Empty),
-                    _ => {return Err(GridPointError::InconsumableError(self.maze[row][col]))}, // Early return so we don't eat the inedible.
-                }
-            };
+        /// Retrieves where the mover ends up and the direction it leaves in after a step from the provided
+        /// position, going through any teleporter it steps onto. Returns None if the mover can't go that way.
+        pub fn try_move(&self, from: &Vector2, direction: Direction, mover: Mover) -> Option<(Vector2, Direction)> {
+            if !self.can_move(from, direction, mover) { return None; }
 
-            self.maze[row][col] = GridPoint::Empty;
-            
-            res 
+            let (to, direction) = self.follow_portal(from.forward(direction), direction);
+            if self.is_valid_pos(&to) { Some((to, direction)) } else { None }
+        }
+
+        /// Retrieves the GridPoint stored at the provided point and replaces it with empty.
+        pub fn eat(&mut self, pos: &Vector2) -> Result<GridPoint, GridPointError> {
+            let cell = self.cell_id(pos).ok_or(GridPointError::BadPosError)?;
+            match &self.layout.cells[cell.0] {
+                point @ (GridPoint::Pellet | GridPoint::PowerPellet) => {
+                    let point = point.clone();
+                    if !self.pellets.remove(cell) { return Ok(GridPoint::Empty); } // Already eaten.
+                    self.pellets_left -= 1;
+                    Ok(point)
+                },
+                GridPoint::Empty => Ok(GridPoint::Empty),
+                point => Err(GridPointError::InconsumableError(point.clone())),
+            }
         }
 
         /// Retrieves the number of pellets left in the maze.
         pub fn pellets_left(&self) -> u32 {
             self.pellets_left
         }
+
+        /// Retrieves the number of pellets the maze started with.
+        pub fn get_starting_pellets(&self) -> u32 {
+            self.layout.starting_pellets.len()
+        }
+
+        /// Retrieves the number of open spaces Clyde can wander to.
+        pub fn get_open_space_count(&self) -> usize {
+            self.open_spaces.len()
+        }
+
+        /// Puts back every pellet that's been eaten, so the maze can be played again without rebuilding it.
+        pub fn reset_pellets(&mut self) {
+            self.pellets = self.layout.starting_pellets.clone();
+            self.pellets_left = self.pellets.len();
+        }
+
+        /// Eats every pellet left in the maze at once.
+        pub fn eat_all(&mut self) {
+            self.pellets = CellSet::new(self.layout.cells.len());
+            self.pellets_left = 0;
+        }
+    }
+
+    impl Default for Grid {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     impl From<Vec<Vec<char>>> for Grid {
         fn from(value: Vec<Vec<char>>) -> Self {
             // Meta data
-            let mut grid = Vec::new();
+            let width = value[0].len();
+            let height = value.len();
+            let mut cells = Vec::with_capacity(width * height);
+            let mut pellets = CellSet::new(width * height);
+            let mut ragged_rows = Vec::new();
             let mut open_spaces = VecDeque::new();
-            let mut pellets_left = 0;
 
-            // Rng is here instead at get_random_position in order to not deal with thread safety.
-            let mut rng = rand::rng();
+            for (row_num, mut row) in value.into_iter().enumerate() { // Y cord
+                if row.len() != width { ragged_rows.push((row_num, row.len())); }
+                row.resize(width, '#'); // Keeps every row the same width so cells can be found by position.
 
-            let mut row_num = 0; // Y cord
-            for row in value {
-                let mut row_collection = Vec::new();
-                let mut col_num = 0; // X cord
-                for col in row {
+                for (col_num, col) in row.into_iter().enumerate() { // X cord
                     let grid_point: GridPoint = col.into();
                     match grid_point {
                         GridPoint::Pellet | GridPoint::PowerPellet | GridPoint:: Empty => {
-                            match grid_point {
-                                GridPoint::Pellet | GridPoint::PowerPellet => pellets_left += 1,
-                                _ => {}
-                            }
-
-                            // "Randomly" shuffle open spaces.
-                            // A pattern is generated where higher and leftmost spaces are more central to the vec
-                            // and that lower and rightmost spaces are more outter to the vec, but it serves its
-                            // purpose for making a "random" position.
-                            if rng.random::<u32>() % 2 == 0 {open_spaces.push_back(Vector2(col_num, row_num));}
-                            else { open_spaces.push_front(Vector2(col_num, row_num)); }
+                            if grid_point != GridPoint::Empty { pellets.insert(CellId(cells.len())); }
+                            open_spaces.push_back(Vector2(col_num as i32, row_num as i32));
                         },
                         _ => {},
                     }
-                    col_num += 1;
-                    row_collection.push(grid_point);
+                    cells.push(grid_point);
                 }
-                row_num += 1;
-                grid.push(row_collection);
             }
 
-            Grid {
-                width: grid[0].len(),
-                height: grid.len(),
-                maze: grid,
-                open_spaces,
-                pellets_left
+            // Defaults to where the characters start in the original maze.
+            let mut spawns = HashMap::new();
+            let mut scatter_positions = HashMap::new();
+            for character in Character::characters() {
+                let data = CharacterData::new(character);
+                spawns.insert(character, data.get_position());
+                scatter_positions.insert(character, data.get_scatter_position());
             }
+
+            let layout = Layout { width, height, cells, starting_pellets: pellets.clone(), ragged_rows, spawns, scatter_positions };
+            let mut res = Grid {
+                layout: Arc::new(layout),
+                pellets_left: pellets.len(),
+                pellets,
+                open_spaces,
+                distances: Arc::default(),
+            };
+
+            // Rng is here instead at get_random_position in order to not deal with thread safety.
+            res.shuffle_open_spaces(&mut rand::rng());
+            res
         }
     }
 
@@ -228,7 +522,7 @@ pub mod grid {
         #[test]
         fn valid_pos() {
             let grid = Grid::new();
-            assert!(grid.is_valid_pos(&Vector2((grid.width - 2) as i32, (grid.height - 2) as i32)));
+            assert!(grid.is_valid_pos(&Vector2((grid.layout.width - 2) as i32, (grid.layout.height - 2) as i32)));
             assert!(grid.is_valid_pos(&Vector2(1, 1)));
         }
 
@@ -239,7 +533,7 @@ pub mod grid {
             let n = grid.open_spaces.len();
             let mut previous_pos = None;
             for _ in 0..n {
-                let pos = grid.get_random_position();
+                let pos = grid.get_random_position().unwrap();
                 assert!(grid.is_valid_pos(&pos));
                 if previous_pos.is_some() {
                     assert_ne!(previous_pos.unwrap(), pos);
@@ -253,11 +547,101 @@ pub mod grid {
         fn invalid_pos() {
             let grid = Grid::new();
             assert!(!grid.is_valid_pos(&Vector2(0, 0)));
-            assert!(!grid.is_valid_pos(&Vector2((grid.width) as i32, (grid.height) as i32)));
-            assert!(!grid.is_valid_pos(&Vector2((grid.width - 1) as i32, (grid.height - 1) as i32)));
+            assert!(!grid.is_valid_pos(&Vector2((grid.layout.width) as i32, (grid.layout.height) as i32)));
+            assert!(!grid.is_valid_pos(&Vector2((grid.layout.width - 1) as i32, (grid.layout.height - 1) as i32)));
             assert!(!grid.is_valid_pos(&Vector2(-1, -1)));
         }
 
+        /// Tests if the nearest open position is found from outside and inside the walls.
+        #[test]
+        fn nearest_pos() {
+            let grid = Grid::new();
+            assert_eq!(grid.nearest_open_pos(Vector2(1, 1)), Some(Vector2(1, 1)));
+            assert_eq!(grid.nearest_open_pos(Vector2(0, 0)), Some(Vector2(1, 1)));
+            assert_eq!(grid.nearest_open_pos(Vector2(26, 0)), Some(Vector2(25, 1)));
+            assert_eq!(grid.nearest_open_pos(Vector2(0, 12)), Some(Vector2(1, 12)));
+            assert_eq!(grid.nearest_open_pos(Vector2(100, 100)), Some(Vector2(25, 26)));
+        }
+
+        /// Tests if stepping onto a teleporter leads to its destination, keeping or replacing the mover's direction.
+        #[test]
+        fn follow_portal() {
+            let mut grid = Grid::new();
+            assert_eq!(grid.follow_portal(Vector2(26, 12), Direction::right()), (Vector2(0, 12), Direction::right()));
+            assert_eq!(grid.follow_portal(Vector2(0, 12), Direction::left()), (Vector2(26, 12), Direction::left()));
+            assert_eq!(grid.follow_portal(Vector2(1, 1), Direction::up()), (Vector2(1, 1), Direction::up()));
+
+            grid.add_portal(Vector2(1, 1), Portal { destination: Vector2(25, 26), exit: Some(Direction::up()), name: None }).unwrap();
+            assert_eq!(grid.pellets_left, 233);
+            assert_eq!(grid.follow_portal(Vector2(1, 1), Direction::left()), (Vector2(25, 26), Direction::up()));
+            assert_eq!(grid.follow_portal(Vector2(25, 26), Direction::down()), (Vector2(25, 26), Direction::down())); // One way.
+        }
+
+        /// Tests if every kind of tile lets the right movers in, going right from the left of it.
+        #[test]
+        fn enter_each_tile() {
+            use Mover::{EatenGhost, Ghost, Rucman};
+            let cases = [
+                ('.', vec![Rucman, Ghost, EatenGhost]),
+                ('*', vec![Rucman, Ghost, EatenGhost]),
+                (' ', vec![Rucman, Ghost, EatenGhost]),
+                ('#', vec![]),
+                ('-', vec![Ghost, EatenGhost]),
+                ('~', vec![Rucman, Ghost, EatenGhost]),
+                ('_', vec![Rucman, Ghost, EatenGhost]),
+                ('>', vec![Rucman, Ghost, EatenGhost]),
+                ('<', vec![]),
+                ('^', vec![]),
+                ('v', vec![]),
+            ];
+
+            for (tile, allowed) in cases {
+                let grid = Grid::from(vec![
+                    "#####".chars().collect(),
+                    vec!['#', ' ', tile, ' ', '#'],
+                    "#####".chars().collect(),
+                ]);
+                for mover in [Rucman, Ghost, EatenGhost] {
+                    assert_eq!(grid.can_move(&Vector2(1, 1), Direction::right(), mover), allowed.contains(&mover), "{mover:?} entering '{tile}'");
+                }
+            }
+        }
+
+        /// Tests if no-up zones and one-way tiles limit which way movers can leave them.
+        #[test]
+        fn leave_special_tiles() {
+            let grid = Grid::from(vec![
+                "#####".chars().collect(),
+                "#   #".chars().collect(),
+                "# _ #".chars().collect(),
+                "# > #".chars().collect(),
+                "#   #".chars().collect(),
+                "#####".chars().collect(),
+            ]);
+
+            // No-up zones
+            assert!(grid.can_move(&Vector2(2, 2), Direction::up(), Mover::Rucman));
+            assert!(!grid.can_move(&Vector2(2, 2), Direction::up(), Mover::Ghost));
+            assert!(grid.can_move(&Vector2(2, 2), Direction::up(), Mover::EatenGhost));
+            assert!(grid.can_move(&Vector2(2, 2), Direction::left(), Mover::Ghost));
+
+            // One-way tiles
+            for mover in [Mover::Rucman, Mover::Ghost, Mover::EatenGhost] {
+                assert!(!grid.can_move(&Vector2(2, 3), Direction::left(), mover));
+                assert!(grid.can_move(&Vector2(2, 3), Direction::right(), mover));
+                assert!(grid.can_move(&Vector2(2, 3), Direction::down(), mover));
+            }
+        }
+
+        /// Tests if moves go through teleporters and stop at walls.
+        #[test]
+        fn try_move() {
+            let grid = Grid::new();
+            assert_eq!(grid.try_move(&Vector2(25, 12), Direction::right(), Mover::Rucman), Some((Vector2(0, 12), Direction::right())));
+            assert_eq!(grid.try_move(&Vector2(1, 1), Direction::right(), Mover::Ghost), Some((Vector2(2, 1), Direction::right())));
+            assert_eq!(grid.try_move(&Vector2(1, 1), Direction::up(), Mover::Ghost), None);
+        }
+
         /// Tests if the grid can accurately return a Grid point on valid positions.
         #[test]
         fn valid_eat() {
@@ -284,14 +668,76 @@ pub mod grid {
             assert_eq!(grid.pellets_left, 232);
         }
 
+        /// Tests if cell ids and positions convert back and forth and stay inside the maze.
+        #[test]
+        fn cell_ids() {
+            let grid = Grid::new();
+            let cell = grid.cell_id(&Vector2(3, 2)).unwrap();
+            assert_eq!(cell.index(), 2 * grid.layout.width + 3);
+            assert_eq!(grid.cell_pos(cell), Vector2(3, 2));
+            assert_eq!(grid.get_cell(cell), GridPoint::Wall);
+            assert_eq!(grid.cell_id(&Vector2(grid.layout.width as i32, 0)), None);
+            assert_eq!(grid.cell_id(&Vector2(0, -1)), None);
+        }
+
+        /// Tests if eaten pellets are tracked and can all be put back.
+        #[test]
+        fn reset_pellets() {
+            let mut grid = Grid::new();
+            let _ = grid.eat(&Vector2(1, 1));
+            let _ = grid.eat(&Vector2(1, 3));
+            assert!(!grid.has_pellet(&Vector2(1, 1)));
+            assert_eq!(grid.get_point(&Vector2(1, 3)), Some(GridPoint::Empty));
+
+            grid.reset_pellets();
+            assert_eq!(grid.pellets_left, 234);
+            assert!(grid.has_pellet(&Vector2(1, 1)));
+            assert_eq!(grid.get_point(&Vector2(1, 3)), Some(GridPoint::PowerPellet));
+            assert_eq!(grid.get_maze(), Grid::new().get_maze());
+        }
+
+        /// Tests if clones share the layout until one of them changes it, and keep their own pellets.
+        #[test]
+        fn clones_share_layout() {
+            let grid = Grid::new();
+            let mut clone = grid.clone();
+            assert!(Arc::ptr_eq(&grid.layout, &clone.layout));
+
+            clone.eat(&Vector2(1, 1)).unwrap();
+            assert!(grid.has_pellet(&Vector2(1, 1)));
+            assert!(Arc::ptr_eq(&grid.layout, &clone.layout));
+
+            clone.set_spawn(Character::Rucman, Vector2(1, 1));
+            assert!(!Arc::ptr_eq(&grid.layout, &clone.layout));
+            assert_eq!(grid.get_spawn(Character::Rucman), Vector2(13, 20));
+        }
+
+        /// Tests if the fallible constructors and portal placement report errors instead of panicking.
+        #[test]
+        fn recoverable_errors() {
+            assert!(matches!(Grid::try_from_rows(vec![]), Err(RucmanError::MazeParse(_))));
+            assert!(matches!(Grid::try_from_rows(vec!["#x#".chars().collect()]), Err(RucmanError::MazeParse(_))));
+            assert_eq!(Grid::try_from_rows(vec!["#.#".chars().collect()]).unwrap().pellets_left, 1);
+
+            let grid = Grid::try_from_str(include_str!("../mazes/classic.maze")).unwrap();
+            assert_eq!(grid.get_maze(), Grid::new().get_maze());
+            assert!(Grid::try_from_str("#####\n#RBx#\n#####\n").is_err());
+
+            let mut grid = Grid::new();
+            let portal = Portal { destination: Vector2(1, 1), exit: None, name: None };
+            assert_eq!(grid.add_portal(Vector2(-1, 5), portal), Err(GridPointError::BadPosError));
+            assert_eq!(grid.add_teleporter_pair(Vector2(1, 1), Vector2(99, 1)), Err(GridPointError::BadPosError));
+            assert_eq!(grid.get_point(&Vector2(1, 1)), Some(GridPoint::Pellet)); // Nothing was placed.
+        }
+
         /// Tests if the grid can accurately return an error on invalid eat positions.
         #[test]
         fn invalid_eat() {
             let mut grid = Grid::new();
             assert_eq!(grid.eat(&Vector2(0, 0)), Err(GridPointError::InconsumableError(GridPoint::Wall)));
-            assert_eq!(grid.eat(&Vector2((grid.width) as i32, (grid.height) as i32)), Err(GridPointError::BadPosError));
+            assert_eq!(grid.eat(&Vector2((grid.layout.width) as i32, (grid.layout.height) as i32)), Err(GridPointError::BadPosError));
             assert_eq!(grid.eat(&Vector2(-1, -1)), Err(GridPointError::BadPosError));
-            assert_eq!(grid.eat(&Vector2((grid.width - 1) as i32, (grid.height - 1) as i32)), Err(GridPointError::InconsumableError(GridPoint::Wall)));
+            assert_eq!(grid.eat(&Vector2((grid.layout.width - 1) as i32, (grid.layout.height - 1) as i32)), Err(GridPointError::InconsumableError(GridPoint::Wall)));
         }
     }
 }
\ No newline at end of file
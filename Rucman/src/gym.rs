@@ -0,0 +1,352 @@
+use serde_json::{json, Map, Value};
+
+use std::io::{BufRead, Write};
+
+use crate::character::{CharacterData, Vulnerability};
+use crate::direction::Direction;
+use crate::error::{Result, RucmanError};
+use crate::events::{EventBus, EventLogger, GameEvent};
+use crate::game::{Game, StepOutcome};
+use crate::level::LevelPack;
+use crate::observation::Observation;
+
+/// How much each thing that can happen in a frame is worth to an agent. Rewards from every frame of a step
+/// are added together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardConfig {
+    pub score: f64, // Per point the score goes up or down by.
+    pub pellet: f64,
+    pub power_pellet: f64,
+    pub death: f64,
+    pub level: f64, // For clearing a level.
+    pub frame: f64, // For every frame played, such as a small penalty to hurry the agent up.
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        RewardConfig { score: 1.0, pellet: 0.0, power_pellet: 0.0, death: 0.0, level: 0.0, frame: 0.0 }
+    }
+}
+
+impl RewardConfig {
+    /// Replaces the rewards named in a JSON object, keeping the rest.
+    pub fn update(&mut self, rewards: &Map<String, Value>) -> Result<()> {
+        for (name, value) in rewards {
+            let value = value.as_f64().ok_or_else(|| invalid(format!("Reward '{name}' needs a number")))?;
+            match name.as_str() {
+                "score" => self.score = value,
+                "pellet" => self.pellet = value,
+                "power_pellet" => self.power_pellet = value,
+                "death" => self.death = value,
+                "level" => self.level = value,
+                "frame" => self.frame = value,
+                _ => return Err(invalid(format!("Unknown reward '{name}'"))),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves what an event is worth, apart from the points it scores.
+    pub fn for_event(&self, event: &GameEvent) -> f64 {
+        match event {
+            GameEvent::PelletEaten { .. } => self.pellet,
+            GameEvent::PowerPelletEaten { .. } => self.power_pellet,
+            GameEvent::RucmanCaught { .. } => self.death,
+            GameEvent::LevelCleared { .. } => self.level,
+            _ => 0.0,
+        }
+    }
+}
+
+/// What happened over the frames of a single step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub observation: Value,
+    pub reward: f64,
+    pub done: bool,
+    pub info: Value,
+}
+
+/// A reinforcement learning environment around the game. Each step turns Rucman and plays out a number of
+/// frames, respawning him when he's caught and moving on when he clears a level. Episodes end on a game over,
+/// clearing the pack or running out of frames.
+#[derive(Debug)]
+pub struct GymEnv {
+    pack: LevelPack,
+    game: Option<Game>,
+    done: bool, // The episode ended and needs a reset.
+    rewards: RewardConfig,
+    frame_skip: u32, // Frames played per step.
+    max_frames: u128, // Episodes still going after this many frames are cut short.
+    planes: bool, // Observations also carry the game encoded as an `Observation`.
+    bus: EventBus, // Hears every event of every episode, after the rewards are counted.
+}
+
+impl GymEnv {
+    /// Creates an environment that plays the provided pack. It has to be reset before stepping.
+    pub fn new(pack: LevelPack) -> Self {
+        let mut bus = EventBus::new();
+        bus.subscribe(EventLogger);
+        GymEnv { pack, game: None, done: false, rewards: RewardConfig::default(), frame_skip: 1, max_frames: 100_000, planes: false, bus }
+    }
+
+    /// Retrieves the game being played, if an episode has started.
+    pub fn get_game(&self) -> Option<&Game> {
+        self.game.as_ref()
+    }
+
+    /// Retrieves what each frame's events are worth.
+    pub fn get_rewards(&self) -> &RewardConfig {
+        &self.rewards
+    }
+
+    /// Sets what each frame's events are worth.
+    pub fn set_rewards(&mut self, rewards: RewardConfig) {
+        self.rewards = rewards;
+    }
+
+    /// Sets how many frames each step plays, at least one.
+    pub fn set_frame_skip(&mut self, frame_skip: u32) {
+        self.frame_skip = frame_skip.max(1);
+    }
+
+    /// Sets how many frames an episode can last.
+    pub fn set_max_frames(&mut self, max_frames: u128) {
+        self.max_frames = max_frames;
+    }
+
+    /// Sets whether observations also carry the game encoded as channel planes, under `planes`.
+    pub fn set_planes(&mut self, planes: bool) {
+        self.planes = planes;
+    }
+
+    /// Starts a new episode. The same seed and actions always play out the same way.
+    pub fn reset(&mut self, seed: u64) -> Result<Value> {
+        let game = Game::with_seed(self.pack.clone(), seed)?;
+        let observation = Self::observe(&game, self.planes);
+        self.game = Some(game);
+        self.done = false;
+        Ok(observation)
+    }
+
+    /// Turns Rucman, if there's an action, and plays out the next frames.
+    pub fn step(&mut self, action: Option<Direction>) -> Result<Transition> {
+        let game = self.game.as_mut().ok_or_else(|| invalid(String::from("Reset the environment before stepping")))?;
+        if self.done { return Err(invalid(String::from("Episode is over, reset the environment"))); }
+
+        if let Some(direction) = action { game.turn(direction); }
+
+        let (mut reward, mut caught_by, mut victory) = (0.0, Vec::new(), false);
+        let mut frames = 0;
+        while frames < self.frame_skip && !game.is_over() && !victory {
+            let score = game.get_number_manager().get_score();
+            let outcome = game.step();
+            frames += 1;
+            reward += self.rewards.frame + game.get_events().iter().map(|event| self.rewards.for_event(event)).sum::<f64>();
+
+            match outcome {
+                StepOutcome::Caught(ghost) => {
+                    caught_by.push(ghost.to_string());
+                    game.respawn();
+                },
+                StepOutcome::LevelComplete => victory = !game.next_level()?,
+                StepOutcome::Continue => {},
+            }
+            game.publish_events(&mut self.bus);
+            reward += self.rewards.score * (game.get_number_manager().get_score() as f64 - score as f64);
+        }
+
+        let truncated = game.get_frames() >= self.max_frames;
+        let info = json!({
+            "frames": frames,
+            "total_frames": game.get_frames() as u64,
+            "caught_by": caught_by,
+            "victory": victory,
+            "truncated": truncated,
+        });
+
+        let done = game.is_over() || victory || truncated;
+        let observation = Self::observe(game, self.planes);
+        self.done = done;
+        Ok(Transition { observation, reward, done, info })
+    }
+
+    /// Describes the game for the agent, with its channel planes if they were asked for.
+    fn observe(game: &Game, planes: bool) -> Value {
+        let mut res = observe(game);
+        if planes {
+            let planes = Observation::encode(game);
+            res["planes"] = json!({ "shape": planes.get_shape(), "data": planes.get_data() });
+        }
+
+        res
+    }
+
+    /// Answers a single request line of the protocol. Bad requests are answered with an error instead of
+    /// stopping the environment. Returns None for a request to close.
+    pub fn handle(&mut self, line: &str) -> Option<Value> {
+        match self.try_handle(line) {
+            Ok(response) => response,
+            Err(err) => Some(json!({ "error": err.to_string() })),
+        }
+    }
+
+    /// Answers a request, failing if it doesn't make sense.
+    fn try_handle(&mut self, line: &str) -> Result<Option<Value>> {
+        let request: Value = serde_json::from_str(line).map_err(|err| invalid(format!("Bad request: {err}")))?;
+        match request["cmd"].as_str() {
+            Some("reset") => {
+                if let Some(rewards) = request.get("rewards") {
+                    let rewards = rewards.as_object().ok_or_else(|| invalid(String::from("Rewards need to be an object")))?;
+                    self.rewards.update(rewards)?;
+                }
+                if let Some(frame_skip) = request.get("frame_skip") {
+                    let frame_skip = frame_skip.as_u64().ok_or_else(|| invalid(String::from("Frame skip needs a number")))?;
+                    self.set_frame_skip(u32::try_from(frame_skip).map_err(|_| invalid(format!("Frame skip {frame_skip} is too big")))?);
+                }
+                if let Some(planes) = request.get("planes") {
+                    self.set_planes(planes.as_bool().ok_or_else(|| invalid(String::from("Planes needs true or false")))?);
+                }
+                if let Some(max_frames) = request.get("max_frames") {
+                    self.set_max_frames(max_frames.as_u64().ok_or_else(|| invalid(String::from("Max frames needs a number")))? as u128);
+                }
+
+                let seed = request.get("seed").map_or(Some(rand::random()), Value::as_u64)
+                    .ok_or_else(|| invalid(String::from("Seed needs a number")))?;
+                Ok(Some(json!({ "observation": self.reset(seed)? })))
+            },
+            Some("step") => {
+                let transition = self.step(parse_action(&request["action"])?)?;
+                Ok(Some(json!({
+                    "observation": transition.observation,
+                    "reward": transition.reward,
+                    "done": transition.done,
+                    "info": transition.info,
+                })))
+            },
+            Some("close") => Ok(None),
+            _ => Err(invalid(String::from("Requests need a cmd of reset, step or close"))),
+        }
+    }
+
+    /// Speaks the protocol until the input runs out or a close request: one JSON request per line in, one JSON
+    /// response per line out.
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() { continue; }
+
+            let Some(response) = self.handle(&line) else { break; };
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads an action, which is a direction's name, its index in `Direction::directions` or null to keep going.
+pub fn parse_action(action: &Value) -> Result<Option<Direction>> {
+    let directions = Direction::directions();
+    match action {
+        Value::Null => Ok(None),
+        Value::String(name) if name == "none" => Ok(None),
+        Value::String(name) => Direction::from_name(name).map(Some).ok_or_else(|| invalid(format!("Unknown action '{name}'"))),
+        Value::Number(index) => index.as_u64().and_then(|index| directions.get(index as usize)).copied().map(Some)
+            .ok_or_else(|| invalid(format!("Action {index} isn't one of the {} directions", directions.len()))),
+        _ => Err(invalid(format!("Unknown action {action}"))),
+    }
+}
+
+/// Describes everything an agent can see: the maze's tiles without anyone on them, where everyone is and
+/// the stats.
+pub fn observe(game: &Game) -> Value {
+    let tiles: Vec<String> = game.get_grid().get_maze().into_iter()
+        .map(|row| row.into_iter().map(char::from).collect())
+        .collect();
+    let entity = |character: &CharacterData| json!({
+        "name": character.get_character().to_string(),
+        "x": character.get_position().0,
+        "y": character.get_position().1,
+        "direction": character.get_direction().to_string(),
+    });
+    let ghosts: Vec<Value> = game.get_ghosts().iter().map(|ghost| {
+        let mut res = entity(ghost);
+        res["vulnerable"] = json!(ghost.get_vulnerability() == Vulnerability::Vulnerable);
+        res
+    }).collect();
+    let number_manager = game.get_number_manager();
+
+    json!({
+        "tiles": tiles,
+        "rucman": entity(game.get_rucman()),
+        "ghosts": ghosts,
+        "score": number_manager.get_score(),
+        "lives": number_manager.get_lives(),
+        "level": number_manager.get_level(),
+        "pellets_left": game.get_grid().pellets_left(),
+    })
+}
+
+/// Creates an error for a request that doesn't make sense.
+fn invalid(message: String) -> RucmanError {
+    RucmanError::InvalidConfig(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if requests get the right responses and seeded episodes repeat.
+    #[test]
+    fn protocol() {
+        let mut env = GymEnv::new(LevelPack::classic());
+        let requests = [
+            r#"{"cmd":"step","action":"up"}"#,
+            r#"{"cmd":"reset","seed":4,"frame_skip":3,"rewards":{"pellet":2.0,"score":0}}"#,
+            r#"{"cmd":"step","action":"left"}"#,
+            r#"{"cmd":"step","action":9}"#,
+            r#"{"cmd":"reset","frame_skip":4294967296}"#,
+            r#"{"cmd":"close"}"#,
+            r#"{"cmd":"reset"}"#,
+        ];
+        let mut output = Vec::new();
+        env.serve(requests.join("\n").as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(responses.len(), 5); // Nothing after the close.
+        assert!(responses[0]["error"].is_string());
+        assert_eq!(responses[1]["observation"]["rucman"]["x"], 13);
+        assert_eq!(responses[1]["observation"]["tiles"].as_array().unwrap().len(), 28);
+        assert_eq!(responses[2]["info"]["frames"], 3);
+        assert_eq!(responses[2]["reward"], 6.0); // Three pellets along the bottom corridor.
+        assert_eq!(responses[2]["observation"]["rucman"]["x"], 10);
+        assert!(responses[3]["error"].as_str().unwrap().contains("Action 9"));
+        assert!(responses[4]["error"].as_str().unwrap().contains("Frame skip 4294967296"));
+
+        let mut replay = GymEnv::new(LevelPack::classic());
+        assert_eq!(replay.reset(4).unwrap(), responses[1]["observation"]);
+        replay.set_planes(true);
+        assert_eq!(replay.reset(4).unwrap()["planes"]["shape"], json!([10, 28, 27]));
+    }
+
+    /// Tests if episodes end on a game over and can't be stepped afterwards.
+    #[test]
+    fn episode_ends() {
+        let mut env = GymEnv::new(LevelPack::classic());
+        env.reset(1).unwrap();
+        env.set_rewards(RewardConfig { death: -100.0, ..RewardConfig::default() });
+
+        let mut deaths = 0;
+        let transition = loop {
+            let transition = env.step(None).unwrap();
+            deaths += transition.info["caught_by"].as_array().unwrap().len();
+            if transition.done { break transition; }
+        };
+        assert_eq!(deaths, 3);
+        assert!(transition.reward <= -100.0);
+        assert_eq!(transition.observation["lives"], 0);
+        assert!(env.step(None).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Result, RucmanError};
+use crate::grid::grid::Grid;
+use crate::managers::MAX_SCATTER_INTERVAL;
+use crate::maze;
+
+/// Settings that replace the usual level progression for a single level.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelOverrides {
+    pub scatter_interval: Option<u128>,
+    pub vulnerability_length: Option<u32>,
+    pub elroy_thresholds: Option<(u32, u32)>,
+}
+
+/// A single level of a level pack.
+#[derive(Debug, Clone)]
+pub struct Level {
+    maze: Option<String>, // Maze file text. None uses the built-in maze.
+    overrides: LevelOverrides,
+}
+
+impl Level {
+    /// Builds a fresh grid for this level.
+    pub fn build_grid(&self) -> Result<Grid> {
+        match &self.maze {
+            Some(text) => maze::parse_maze(text),
+            None => Ok(Grid::new()),
+        }
+    }
+
+    /// Borrow the settings this level overrides.
+    pub fn get_overrides(&self) -> &LevelOverrides {
+        &self.overrides
+    }
+}
+
+/// An ordered list of levels that are played one after another.
+#[derive(Debug, Clone)]
+pub struct LevelPack {
+    name: String,
+    levels: Vec<Level>,
+    wrap: bool,
+}
+
+impl LevelPack {
+    /// Creates the default pack which plays the built-in maze forever.
+    pub fn classic() -> Self {
+        LevelPack {
+            name: String::from("Classic"),
+            levels: vec![Level { maze: None, overrides: LevelOverrides::default() }],
+            wrap: true,
+        }
+    }
+
+    /// Loads a level pack file from disk. Maze paths are relative to the pack file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parses the text of a level pack file.
+    ///
+    /// Pack settings (`name` and `wrap`) come first as `key = value` lines. Every `[level]` line starts a new
+    /// level which needs a `maze` and may override `scatter_interval` (1 to 120 frames), `vulnerability_length` and
+    /// `elroy_pellets` (two numbers for stages one and two). Lines starting with `;` are comments.
+    /// Every maze is loaded and checked up front so a broken pack fails before the game starts.
+    pub fn parse(text: &str, maze_dir: &Path) -> Result<Self> {
+        let mut name = String::from("Untitled");
+        let mut wrap = false;
+        let mut levels: Vec<Level> = Vec::new();
+
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') { continue; }
+
+            if line == "[level]" {
+                levels.push(Level { maze: None, overrides: LevelOverrides::default() });
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| invalid(format!("Line {}: expected 'key = value'", line_num + 1)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let Some(level) = levels.last_mut() else {
+                match key {
+                    "name" => name = value.to_string(),
+                    "wrap" => wrap = parse_value(value, line_num)?,
+                    _ => return Err(invalid(format!("Line {}: unknown pack setting '{key}'", line_num + 1))),
+                }
+                continue;
+            };
+
+            match key {
+                "maze" => {
+                    let text = fs::read_to_string(maze_dir.join(value))?;
+                    maze::parse_maze(&text)
+                        .map_err(|err| invalid(format!("Line {}: maze '{value}': {err}", line_num + 1)))?;
+                    level.maze = Some(text);
+                },
+                "scatter_interval" => {
+                    let scatter_interval = parse_value(value, line_num)?;
+                    if !(1..=MAX_SCATTER_INTERVAL).contains(&scatter_interval) {
+                        return Err(invalid(format!("Line {}: scatter_interval must be from 1 to {MAX_SCATTER_INTERVAL}", line_num + 1)));
+                    }
+                    level.overrides.scatter_interval = Some(scatter_interval);
+                },
+                "vulnerability_length" => level.overrides.vulnerability_length = Some(parse_value(value, line_num)?),
+                "elroy_pellets" => {
+                    let thresholds: Vec<u32> = value.split_whitespace()
+                        .map(|threshold| parse_value(threshold, line_num))
+                        .collect::<Result<_>>()?;
+                    match thresholds.as_slice() {
+                        [stage_one, stage_two] => level.overrides.elroy_thresholds = Some((*stage_one, *stage_two)),
+                        _ => return Err(invalid(format!("Line {}: elroy_pellets needs 2 numbers", line_num + 1))),
+                    }
+                },
+                _ => return Err(invalid(format!("Line {}: unknown level setting '{key}'", line_num + 1))),
+            }
+        }
+
+        if levels.is_empty() { return Err(invalid(String::from("Pack has no levels"))); }
+        if levels.iter().any(|level| level.maze.is_none()) {
+            return Err(invalid(String::from("Every level needs a maze")));
+        }
+
+        Ok(LevelPack { name, levels, wrap })
+    }
+
+    /// Retrieves the name of the pack.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retrieves how many levels the pack has before it wraps or runs out.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Retrieves the level to play for the provided level number, starting at 1.
+    /// Returns None once the last level is cleared in a pack that doesn't wrap.
+    pub fn get_level(&self, level: u32) -> Option<&Level> {
+        let index = level.saturating_sub(1) as usize;
+        if self.wrap {
+            self.levels.get(index % self.levels.len())
+        }
+        else {
+            self.levels.get(index)
+        }
+    }
+}
+
+/// Parses a setting's value, reporting the line it came from on failure.
+fn parse_value<T: std::str::FromStr>(value: &str, line_num: usize) -> Result<T> {
+    value.parse().map_err(|_| invalid(format!("Line {}: bad value '{value}'", line_num + 1)))
+}
+
+/// Creates an invalid configuration error with the provided message.
+fn invalid(message: String) -> RucmanError {
+    RucmanError::InvalidConfig(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if the bundled pack loads with its overrides.
+    #[test]
+    fn load_arcade_pack() {
+        let pack = LevelPack::load(Path::new("packs/arcade.pack")).unwrap();
+        assert_eq!(pack.get_name(), "Arcade");
+        assert_eq!(pack.levels.len(), 2);
+        assert_eq!(pack.get_level(1).unwrap().get_overrides(), &LevelOverrides::default());
+        assert_eq!(pack.get_level(2).unwrap().get_overrides().elroy_thresholds, Some((15, 8)));
+        assert_eq!(pack.get_level(2).unwrap().get_overrides().scatter_interval, Some(60));
+        assert_eq!(pack.get_level(1).unwrap().build_grid().unwrap().get_width(), 27);
+        assert_eq!(pack.get_level(2).unwrap().build_grid().unwrap().get_width(), 21);
+    }
+
+    /// Tests if levels wrap around or run out depending on the pack.
+    #[test]
+    fn level_rotation() {
+        let pack = LevelPack::load(Path::new("packs/arcade.pack")).unwrap();
+        assert_eq!(pack.get_level(3).unwrap().get_overrides(), pack.get_level(1).unwrap().get_overrides());
+        assert_eq!(pack.get_level(4).unwrap().get_overrides(), pack.get_level(2).unwrap().get_overrides());
+
+        let text = "name = Short\n[level]\nmaze = mazes/lattice.maze\n";
+        let pack = LevelPack::parse(text, Path::new("")).unwrap();
+        assert!(pack.get_level(1).is_some());
+        assert!(pack.get_level(2).is_none());
+
+        assert!(LevelPack::classic().get_level(100).is_some());
+    }
+
+    /// Tests if malformed packs are rejected.
+    #[test]
+    fn parse_errors() {
+        assert!(LevelPack::parse("name = Empty\n", Path::new("")).is_err());
+        assert!(LevelPack::parse("[level]\n", Path::new("")).is_err());
+        assert!(LevelPack::parse("[level]\nmaze = mazes/missing.maze\n", Path::new("")).is_err());
+        assert!(LevelPack::parse("[level]\nmaze = mazes/lattice.maze\nelroy_pellets = 5\n", Path::new("")).is_err());
+        assert!(LevelPack::parse("speed = 9\n", Path::new("")).is_err());
+
+        let scatter = |value: &str| LevelPack::parse(&format!("[level]\nmaze = mazes/lattice.maze\nscatter_interval = {value}\n"), Path::new(""));
+        assert!(scatter("120").is_ok());
+        for value in ["0", "121", "340282366920938463463374607431768211455"] {
+            let err = scatter(value).unwrap_err();
+            assert!(matches!(&err, RucmanError::InvalidConfig(message) if message.starts_with("Line 3: scatter_interval")), "{err}");
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! The game logic, mazes and tools behind Rucman. The Rucman executable plays the game on top of this library.
+
+pub mod error;
+pub mod grid;
+pub mod point;
+pub mod direction;
+pub mod character;
+pub mod a_star;
+pub mod distance;
+pub mod managers;
+pub mod game;
+pub mod camera;
+pub mod render;
+pub mod terminal;
+pub mod maze;
+pub mod level;
+pub mod scores;
+pub mod validate;
+pub mod generator;
+pub mod editor;
+pub mod bot;
+pub mod simulate;
+pub mod gym;
+pub mod observation;
+pub mod mcts;
+pub mod debugger;
+pub mod console;
+pub mod logging;
+pub mod events;
+pub mod audio;
@@ -0,0 +1,103 @@
+use tracing::Subscriber;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::MakeWriter;
+
+use std::env;
+use std::fs::OpenOptions;
+use std::sync::Mutex;
+
+use crate::error::{Result, RucmanError};
+
+/// Where logs are written unless `RUCMAN_LOG_FILE` says otherwise.
+pub const LOG_FILE: &str = "rucman.log";
+
+/// What gets logged unless `RUCMAN_LOG` says otherwise: problems only, such as ghosts that can't find a path.
+pub const DEFAULT_FILTER: &str = "warn";
+
+/// Parses filter directives such as `info` or `warn,rucman::events=trace`: a level for everything, then levels for
+/// single modules. Game events are logged under `rucman::events`, inside a span with the frame they happened on,
+/// and pathfinding failures under `rucman::character` and `rucman::a_star`.
+pub fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::builder().parse(directives).map_err(|err| RucmanError::InvalidConfig(format!("Bad log filter '{directives}': {err}")))
+}
+
+/// Creates a subscriber that writes every event the filter lets through as a line of plain text, with the time,
+/// level, module and the event's fields.
+pub fn subscriber<W>(filter: EnvFilter, writer: W) -> impl Subscriber + Send + Sync
+where W: for<'a> MakeWriter<'a> + Send + Sync + 'static {
+    tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer).with_ansi(false).finish()
+}
+
+/// Starts logging to a file for the rest of the program, never to the terminal so nothing is drawn over the game.
+/// `RUCMAN_LOG` sets the filter and `RUCMAN_LOG_FILE` the file, which is appended to.
+pub fn init() -> Result<()> {
+    let filter = parse_filter(&env::var("RUCMAN_LOG").unwrap_or_else(|_| String::from(DEFAULT_FILTER)))?;
+    let path = env::var("RUCMAN_LOG_FILE").unwrap_or_else(|_| String::from(LOG_FILE));
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    tracing::subscriber::set_global_default(subscriber(filter, Mutex::new(file)))
+        .map_err(|err| RucmanError::InvalidConfig(format!("Logging was already started: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBus, EventLogger};
+    use crate::game::{Game, StepOutcome};
+    use crate::level::LevelPack;
+
+    use std::io::{self, Write};
+    use std::sync::Arc;
+
+    /// Collects logs in memory.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests if game events are logged with their fields, and if module filters choose which ones are.
+    #[test]
+    fn filtered_events() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = subscriber(parse_filter("warn,rucman::game=info,rucman::events=info").unwrap(), move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut game = Game::with_seed(LevelPack::classic(), 2).unwrap();
+            let mut bus = EventBus::new();
+            bus.subscribe(EventLogger);
+            loop {
+                let outcome = game.step();
+                game.publish_events(&mut bus);
+                if matches!(outcome, StepOutcome::Caught(_)) { break; }
+            }
+        });
+
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("rucman::events: Rucman caught by="), "{logs}");
+        assert!(logs.contains("frame{frame="));
+        assert!(!logs.contains("Pellet eaten")); // Traced, which is below info.
+        assert!(!logs.contains("Mode changed")); // Debug, which is below info.
+        assert!(!logs.contains('\x1b'));
+
+        // Events only reach the log through a bus, so games played out by bots on the side stay quiet.
+        let quiet = Buffer::default();
+        let writer = quiet.clone();
+        tracing::subscriber::with_default(super::subscriber(parse_filter("info").unwrap(), move || writer.clone()), || {
+            let mut game = Game::with_seed(LevelPack::classic(), 2).unwrap();
+            while !matches!(game.step(), StepOutcome::Caught(_)) {}
+        });
+        assert!(!String::from_utf8(quiet.0.lock().unwrap().clone()).unwrap().contains("rucman::events"));
+
+        assert!(parse_filter("rucman::game=loud").is_err());
+    }
+}
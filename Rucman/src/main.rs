@@ -1,313 +1,534 @@
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType};
-use crossterm::cursor;
 use crossterm::event::{read, Event, KeyCode, KeyModifiers};
 use crossterm::execute;
+use crossterm::terminal;
 use crossterm::style::Print;
-
-use std::sync::{Arc, Mutex};
+use tracing::error;
+
+use std::env;
+use std::fmt::Display;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use std::io::{self, Stdout, stdout, stderr};
+use std::io::{self, Stdout, stdout};
 use std::thread;
 use std::thread::{sleep, JoinHandle};
 
-mod grid;
-use grid::grid::{Grid, GridPoint, GridPointError}; //grid.rs -> mod grid -> Grid stuct et al
+use rucman::direction::Direction;
+use rucman::game::{Game, StepOutcome};
+use rucman::managers::NumberManager;
+use rucman::level::LevelPack;
+use rucman::scores::{HighScore, HighScores, HIGH_SCORE_FILE};
+use rucman::validate::{has_errors, validate_maze_file, Severity};
+use rucman::generator::{generate, MazeConfig};
+use rucman::maze::to_maze_text;
+use rucman::editor;
+use rucman::camera::Camera;
+use rucman::render::{draw_frame, draw_lines, render_frame_with_footer, render_victory_screen, StatusMessages, TerminalSize};
+use rucman::terminal::TerminalGuard;
+use rucman::bot::{Bot, GreedyBot};
+use rucman::gym::GymEnv;
+use rucman::simulate::{simulate, to_csv, to_json, GameSummary, SimulationConfig};
+use rucman::debugger::{inspect, Timeline};
+use rucman::console::Console;
+use rucman::logging;
+use rucman::events::{EventBus, EventLogger};
+use rucman::audio::TerminalBell;
 
-mod point;
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = logging::init() { eprintln!("Not logging: {err}"); }
+
+    // Load the level pack before entering raw mode so errors print normally.
+    match args.first().map(|arg| arg.as_str()) {
+        Some("validate") => validate(&args[1..]),
+        Some("generate") => generate_maze(&args[1..]),
+        Some("edit") => edit_maze(&args[1..]),
+        Some("simulate") => simulate_games(&args[1..]),
+        Some("gym") => gym(&args[1..]),
+        Some("debug") => debug(&args[1..]),
+        Some(path) => play(LevelPack::load(Path::new(path))?),
+        None => play(LevelPack::classic()),
+    }
+}
 
-mod direction;
-use direction::Direction;
+/// Checks each maze file and prints its problems. Exits with an error code if any maze has errors.
+fn validate(paths: &[String]) -> io::Result<()> {
+    if paths.is_empty() {
+        eprintln!("Usage: Rucman validate <maze file>...");
+        process::exit(2);
+    }
 
-mod character;
-use character::{Character, CharacterData, Vulnerability};
+    let mut failed = false;
+    for path in paths {
+        let diagnostics = validate_maze_file(Path::new(path))?;
+        for diagnostic in &diagnostics {
+            println!("{path}: {diagnostic}");
+        }
 
-mod a_star;
+        let errors = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count();
+        println!("{path}: {errors} error(s), {} warning(s)", diagnostics.len() - errors);
+        failed |= has_errors(&diagnostics);
+    }
 
-mod managers;
-use managers::{EntityManager, NumberManager};
+    if failed { process::exit(1); }
+    Ok(())
+}
 
-fn main() -> io::Result<()> {
+/// Generates a maze and prints it or writes it to a file.
+/// Takes the optional flags --seed, --width, --height, --tunnels and --output.
+fn generate_maze(args: &[String]) -> io::Result<()> {
+    let mut config = MazeConfig::default();
+    let mut seed: u64 = rand::random();
+    let mut output = None;
+
+    for (name, value) in flags(args, "Usage: Rucman generate [--seed N] [--width N] [--height N] [--tunnels N] [--output FILE]") {
+        match name {
+            "--seed" => seed = number(name, value)?,
+            "--width" => config.width = number(name, value)?,
+            "--height" => config.height = number(name, value)?,
+            "--tunnels" => config.tunnels = number(name, value)?,
+            "--output" => output = Some(value.clone()),
+            _ => return Err(invalid_input(format!("Unknown flag '{name}'"))),
+        }
+    }
 
-    // Initialize data and game environment.
-    enable_raw_mode()?;
+    let grid = generate(&config, seed)?;
+    let text = format!("; Generated with seed {seed}\n{}", to_maze_text(&grid));
+    match output {
+        Some(path) => std::fs::write(path, text),
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
 
-    let mut stdout = stdout();
-    let frame_sleep = Duration::new(0, 250_000_000);
-    let three_seconds = Duration::new(3, 0);
+/// Plays many games with a bot as fast as possible and writes how each went as CSV or JSON.
+/// Takes the optional flags --pack, --games, --seed, --bot, --ticks, --format and --output.
+fn simulate_games(args: &[String]) -> io::Result<()> {
+    let mut config = SimulationConfig { seed: rand::random(), ..SimulationConfig::default() };
+    let mut pack = LevelPack::classic();
+    let mut json = false;
+    let mut output = None;
+
+    for (name, value) in flags(args, "Usage: Rucman simulate [--pack FILE] [--games N] [--seed N] [--bot NAME] [--ticks N] [--format csv|json] [--output FILE]") {
+        match name {
+            "--pack" => pack = LevelPack::load(Path::new(value))?,
+            "--games" => config.games = number(name, value)?,
+            "--seed" => config.seed = number(name, value)?,
+            "--bot" => config.bot = value.clone(),
+            "--ticks" => config.max_ticks = number(name, value)?,
+            "--format" => json = match value.as_str() {
+                "csv" => false,
+                "json" => true,
+                _ => return Err(invalid_input(format!("Unknown format '{value}'"))),
+            },
+            "--output" => output = Some(value.clone()),
+            _ => return Err(invalid_input(format!("Unknown flag '{name}'"))),
+        }
+    }
 
-    let grid = Grid::new();
+    let summaries = simulate(&pack, &config)?;
+    let text = if json { to_json(&summaries) } else { to_csv(&summaries) };
+    match output {
+        Some(path) => std::fs::write(path, text)?,
+        None => print!("{text}"),
+    }
 
-    let rucman = CharacterData::new(Character::Rucman);
-    let ghosts = vec![
-        CharacterData::new(Character::Inky),
-        CharacterData::new(Character::Blinky),
-        CharacterData::new(Character::Pinky),
-        CharacterData::new(Character::Clyde),
-    ];
+    // Averages go to stderr so they don't end up in piped output.
+    let games = summaries.len().max(1) as f64;
+    let mean = |stat: fn(&GameSummary) -> f64| summaries.iter().map(stat).sum::<f64>() / games;
+    eprintln!(
+        "{} games with seeds from {}: mean score {:.1}, level {:.2}, deaths {:.2}, pellets per life {:.1}, ticks {:.0}",
+        summaries.len(), config.seed, mean(|game| game.score as f64), mean(|game| game.level as f64),
+        mean(|game| game.get_total_deaths() as f64), mean(|game| game.pellets_per_life), mean(|game| game.ticks as f64),
+    );
 
-    let entity_manager = EntityManager {
-        grid: Arc::new(Mutex::new(grid)),
-        rucman: Arc::new(Mutex::new(rucman)),
-        ghosts: Arc::new(Mutex::new(ghosts)), 
-    };
+    Ok(())
+}
 
-    let mut number_manager = NumberManager::new();
+/// Runs a reinforcement learning environment speaking line delimited JSON over stdin and stdout.
+/// Takes the optional flags --pack, --frame-skip and --max-frames.
+fn gym(args: &[String]) -> io::Result<()> {
+    let mut pack = LevelPack::classic();
+    let (mut frame_skip, mut max_frames) = (1, 100_000);
+
+    for (name, value) in flags(args, "Usage: Rucman gym [--pack FILE] [--frame-skip N] [--max-frames N]") {
+        match name {
+            "--pack" => pack = LevelPack::load(Path::new(value))?,
+            "--frame-skip" => frame_skip = number(name, value)?,
+            "--max-frames" => max_frames = number(name, value)?,
+            _ => return Err(invalid_input(format!("Unknown flag '{name}'"))),
+        }
+    }
 
-    let mut frames: u128 = 0;
+    let mut env = GymEnv::new(pack);
+    env.set_frame_skip(frame_skip);
+    env.set_max_frames(max_frames);
+    Ok(env.serve(io::stdin().lock(), stdout().lock())?)
+}
 
-    let input_thread = create_input_controller(&entity_manager);
+/// Steps through a game one tick per key press with the debug overlay on, showing the full state of the game
+/// underneath. Any earlier tick can be gone back to, and playing on from it with a new input starts a new recording.
+/// Takes the optional flags --pack, --seed and --interval, the ticks between snapshots.
+fn debug(args: &[String]) -> io::Result<()> {
+    let mut pack = LevelPack::classic();
+    let (mut seed, mut interval) = (rand::random(), 50);
+
+    for (name, value) in flags(args, "Usage: Rucman debug [--pack FILE] [--seed N] [--interval N]") {
+        match name {
+            "--pack" => pack = LevelPack::load(Path::new(value))?,
+            "--seed" => seed = number(name, value)?,
+            "--interval" => interval = number(name, value)?,
+            _ => return Err(invalid_input(format!("Unknown flag '{name}'"))),
+        }
+    }
 
-    // Main game loop.
-    while number_manager.get_lives() > 0 {        
-        if input_thread.is_finished() { break; } // Stop the game if input thread is ever finished.
+    let mut timeline = Timeline::new(Game::with_seed(pack, seed)?, interval);
+    let _terminal = TerminalGuard::enter()?;
+    let mut stdout = stdout();
+    let mut size = terminal::size()?;
+    let mut camera = Camera::default();
+    let mut input = None; // Turns Rucman on the next step.
+    let mut typed = String::new(); // A tick being typed in to go to.
+
+    loop {
+        let game = timeline.get_game();
+        let mut info: Vec<String> = vec![String::new()];
+        info.extend(inspect(game));
+        info.push(format!(
+            "Tick {}/{} | Seed {seed} | Next input {} | Go to {}",
+            timeline.get_tick(), timeline.get_length(), input.map_or("none".to_string(), |direction: Direction| direction.to_string()), typed,
+        ));
+        if timeline.is_finished() {
+            let ending = if game.is_won() { "Rucman won" } else { "Game over" };
+            info.push(format!("{ending}, so there's nothing left to step. Rewind to play on differently."));
+        }
+        info.push("n/Space step | WASD set input | b back | B back 10 | digits then g go to tick | q quit".to_string());
 
-        // Get the ability to modify rucman and the ghosts.
-        let mut rucman = entity_manager.rucman.lock().unwrap();
-        let mut ghosts = entity_manager.ghosts.lock().unwrap();
-        let mut grid = entity_manager.grid.lock().unwrap();
-
-        // Move rucman (rucman's direction is controlled by the thread.)
-        rucman.rucman_move(&grid);
-
-        //Eat pellets
-        let eatten = grid.eat(&rucman.get_position());
-        match eatten {
-            Ok(pellet) => {
-                match pellet {
-                    GridPoint::Pellet => number_manager.add_score(5),
-                    GridPoint::PowerPellet => {
-                        for ghost in ghosts.iter_mut() {
-                            ghost.set_vulnerable();
-                        }
-                        number_manager.start_vulnerability_timer();
-                        number_manager.add_score(10);
-                    },
-                    _ => {}, // GridPoint empty and anything that eat doesn't denote as inedible.
-                }
+        draw_lines(&mut stdout, render_frame_with_footer(game, size, "DEBUG", &mut camera, true, &info))?;
+
+        let key = match read()? {
+            Event::Key(key) if !key.is_release() => key,
+            Event::Resize(columns, rows) => {
+                size = (columns, rows);
+                continue;
             },
-            Err(invalid) => {
-                match invalid {
-                    GridPointError::InconsumableError(attempt) => {
-                        match attempt {
-                            GridPoint::Teleporter(other) => rucman.set_position(other), // Should be the only inediable object to worry about.
-                            _ => {},
-                        }
-                    }
-                    _ => {},
-                }
+            _ => continue,
+        };
+
+        let tick = timeline.get_tick();
+        match key.code {
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => break,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('n') | KeyCode::Char(' ') if !timeline.is_finished() => {
+                timeline.advance(input.take())?;
+            },
+            KeyCode::Char('w') => input = Some(Direction::up()),
+            KeyCode::Char('a') => input = Some(Direction::left()),
+            KeyCode::Char('s') => input = Some(Direction::down()),
+            KeyCode::Char('d') => input = Some(Direction::right()),
+            KeyCode::Char('b') => timeline.seek(tick.saturating_sub(1))?,
+            KeyCode::Char('B') => timeline.seek(tick.saturating_sub(10))?,
+            KeyCode::Char(digit) if digit.is_ascii_digit() => typed.push(digit),
+            KeyCode::Backspace => { typed.pop(); },
+            KeyCode::Char('g') | KeyCode::Enter => {
+                let target = typed.parse().unwrap_or(tick).min(timeline.get_length());
+                timeline.seek(target)?;
+                typed.clear();
             },
+            _ => {},
         }
+    }
 
-        // Check if rucman ran into a ghost.
-        if let Some(ghost) = check_collision(&mut rucman, &mut ghosts, &mut number_manager) {
-            print_screen(&mut stdout, &grid, &rucman, &ghosts, &number_manager)?;
-            execute!(stdout, Print(format!("Caught by: {:?}", ghost)))?;
-            sleep(three_seconds);
-            reset_characters(&mut rucman, &mut ghosts);
-        }
+    Ok(())
+}
 
-        //Move Ghosts
-        for ghost in ghosts.iter_mut() {
-            match ghost.get_vulnerability() {
-                Vulnerability::Invulnerable => ghost.ghost_move(&mut grid, rucman.get_position(), rucman.get_direction()),
-                Vulnerability::Vulnerable => { // To make vulnerable ghosts slower, they only move on even frames.
-                    if frames % 2 == 0 {
-                        ghost.ghost_move(&mut grid, rucman.get_position(), rucman.get_direction());
-                    }
-                    if number_manager.is_vulnerability_over() {
-                        ghost.set_invulnerable();
-                    }
-                }
-            }            
-        }
+/// Opens the maze editor on a maze file. New mazes take the optional flags --width and --height.
+fn edit_maze(args: &[String]) -> io::Result<()> {
+    let usage = "Usage: Rucman edit <maze file> [--width N] [--height N]";
+    let Some((path, args)) = args.split_first() else {
+        eprintln!("{usage}");
+        process::exit(2);
+    };
 
-        // Copy and pasted.
-        // Check if a ghost ran into rucman.
-        if let Some(ghost) = check_collision(&mut rucman, &mut ghosts, &mut number_manager) {
-            print_screen(&mut stdout, &grid, &rucman, &ghosts, &number_manager)?;
-            execute!(stdout, Print(format!("Caught by: {:?}", ghost)))?;
-            sleep(three_seconds);
-            reset_characters(&mut rucman, &mut ghosts);
+    let (mut width, mut height) = (27, 31);
+    for (name, value) in flags(args, usage) {
+        match name {
+            "--width" => width = number(name, value)?,
+            "--height" => height = number(name, value)?,
+            _ => return Err(invalid_input(format!("Unknown flag '{name}'"))),
         }
+    }
 
-        // Update time data.
-        number_manager.tick_vulernability_timer();
-        if frames == u128::MAX { frames = 0; } //Probably would never happen. Essentially overflow anyway, but this is to define what to happen on overflow.
-        else { frames += 1; }
+    editor::run(Path::new(path), width, height)
+}
 
-        // Scatter ghosts on time.
-        if frames % number_manager.get_scatter_interval() == 0 {
-            for ghost in ghosts.iter_mut() {
-                ghost.set_scatter_mode();
-            }
-        }
+/// Pairs up command line flags with their values. Prints the usage and exits if a flag is missing its value.
+fn flags<'a>(args: &'a [String], usage: &str) -> Vec<(&'a str, &'a String)> {
+    args.chunks(2)
+        .map(|flag| match flag {
+            [name, value] => (name.as_str(), value),
+            _ => {
+                eprintln!("{usage}");
+                process::exit(2);
+            },
+        })
+        .collect()
+}
 
-        // Level completion.
-        if grid.pellets_left() == 0 { 
-            print_screen(&mut stdout, &grid, &rucman, &ghosts, &number_manager)?;
-            execute!(stdout, Print("Level complete!"))?;
-            sleep(three_seconds);
-            reset_game(&mut grid, &mut rucman, &mut ghosts); 
+/// Parses a flag's value as the kind of number it's stored as, failing rather than cutting it down to size if it
+/// doesn't fit.
+fn number<T: FromStr>(name: &str, value: &str) -> io::Result<T>
+where T::Err: Display {
+    value.parse().map_err(|err| invalid_input(format!("{name} needs a number, got '{value}': {err}")))
+}
 
-            // Level up
-            number_manager.level_up();
-        }
+/// Creates an error for a command line that doesn't make sense.
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
 
-        print_screen(&mut stdout, &grid, &rucman, &ghosts, &number_manager)?;
-        
-        // Frees up locks
-        drop(rucman);
-        drop(grid);
-        drop(ghosts);
+/// Plays through the provided level pack until Rucman runs out of lives or clears the pack.
+fn play(pack: LevelPack) -> io::Result<()> {
+    // Initialize data and game environment.
+    let game = Arc::new(Mutex::new(Game::new(pack.clone())?));
+    let terminal = TerminalGuard::enter()?;
+    let size = Arc::new(Mutex::new(terminal::size()?)); // Kept up to date by the input thread.
+    let controls = Arc::new(Controls::default());
 
-        // So the game doesn't do every frame in a single frame.
-        sleep(frame_sleep);
-    }
+    let mut stdout = stdout();
+    let frame_sleep = Duration::new(0, 250_000_000);
+    let console_sleep = Duration::new(0, 50_000_000);
+    let three_seconds = Duration::new(3, 0);
+    let mut victory = false;
+    let mut camera = Camera::default();
 
-    execute!(stdout, Print(format!("Game over! Score: {}\n", number_manager.get_score())))?;
+    let input_thread = create_input_controller(&game, &size, &controls);
 
-    // Make sure we don't get an orphan thread.
-    if !input_thread.is_finished() {
-        execute!(stdout, Print(format!("Press Ctrl+C to end game.\n")))?;
-        let _ = input_thread.join();
-    }
+    // Everything that reacts to what happens in the game hears about it through the bus.
+    let status = Arc::new(Mutex::new(StatusMessages::new()));
+    let mut bus = EventBus::new();
+    bus.subscribe(status.clone());
+    bus.subscribe(TerminalBell::new(io::stdout()));
+    bus.subscribe(EventLogger);
 
-    disable_raw_mode()?;
+    // Quitting from the title screen leaves without a game to show the results of.
+    if !attract_mode(&mut stdout, &pack, &size, &controls, &input_thread)? { return Ok(()); }
 
-    Ok(())
-}
+    // Main game loop.
+    loop {
+        if input_thread.is_finished() { break; } // Stop the game if input thread is ever finished.
 
-/// Prints the screen
-fn print_screen(stdout: &mut Stdout, grid: &Grid, rucman: &CharacterData, ghosts: &Vec<CharacterData>, score_manager: &NumberManager) -> io::Result<()> {        
-    let level = score_manager.get_level();
-    let score = score_manager.get_score();
-    let lives = score_manager.get_lives();
-    let one_up_score = score_manager.get_one_up_score();
-
-    let mut pass_one = Vec::new();
-
-    /* What we're doing here is like painting a landscape. We start with painting the background
-    and then we put the details and subjects over it.*/
-    // Collect the maze (the background)
-    for row in grid.get_maze() {
-        let mut row_collect = Vec::new();
-        for col in row {
-            row_collect.push(char::from(*col));
+        // Get the ability to modify the game.
+        let mut game = lock(&game);
+        if game.is_over() { break; }
+
+        let size = *lock(&size);
+        let overlay = controls.overlay.load(Ordering::Relaxed);
+
+        // Nothing moves while the console is open, but it's redrawn often so typing shows up quickly.
+        let console = lock(&controls.console);
+        if console.is_open() {
+            let lines = render_frame_with_footer(&game, size, "CONSOLE - Esc to close", &mut camera, overlay, &console.lines());
+            drop(console);
+            drop(game);
+            draw_lines(&mut stdout, lines)?;
+            sleep(console_sleep);
+            continue;
         }
-        pass_one.push(row_collect);
-    }
+        drop(console);
+
+        let outcome = game.step();
+        game.publish_events(&mut bus);
+        match outcome {
+            StepOutcome::Caught(_) => {
+                draw_frame(&mut stdout, &game, size, lock(&status).get_message(), &mut camera, overlay)?;
+                sleep(three_seconds);
+                game.respawn();
+            },
+            StepOutcome::LevelComplete => {
+                draw_frame(&mut stdout, &game, size, lock(&status).get_message(), &mut camera, overlay)?;
+                sleep(three_seconds);
+
+                // Advance to the pack's next maze or win if there isn't one.
+                if !game.next_level()? {
+                    victory = true;
+                    break;
+                }
+                game.publish_events(&mut bus);
+            },
+            StepOutcome::Continue => {},
+        }
+
+        draw_frame(&mut stdout, &game, size, lock(&status).get_message(), &mut camera, overlay)?;
+        lock(&status).tick();
 
-    // Place the ghosts and rucman over the maze (the subjects)
-    let pos = rucman.get_position();
-    pass_one[pos.1 as usize][pos.0 as usize] = char::from(rucman);
-    
-    // Ghosts second so they overlap Rucman so its obvious when rucman is dead.
-    for ghost in ghosts {
-        let pos = ghost.get_position();
-        pass_one[pos.1 as usize][pos.0 as usize] = char::from(ghost);
+        // Frees up the lock
+        drop(game);
+
+        // So the game doesn't do every frame in a single frame.
+        sleep(frame_sleep);
     }
-    
-    // Convert collected data into strings and print it.
-    let mut result_string = String::new();
-    let mut i = 0;
-    for row in pass_one {
-        let mut row_string: String = row.iter().collect();
-        match i {
-            1 => row_string.push_str(format!(" Level: {level}").as_str()),
-            2 => row_string.push_str(format!(" Score: {score}").as_str()),
-            3 => row_string.push_str(format!(" Lives: {lives}").as_str()),
-            4 => row_string.push_str(format!(" One up at: {one_up_score}").as_str()),
-            _ => {}
+
+    // Show the victory screen for a few seconds, keeping up with resizes, unless the player quits.
+    if victory {
+        for _ in 0..20 {
+            if input_thread.is_finished() { break; }
+            let lines = render_victory_screen(&lock(&game), *lock(&size), &mut camera);
+            draw_lines(&mut stdout, lines)?;
+            sleep(frame_sleep);
         }
-        
-        row_string.push('\n');
+    }
+
+    let game = lock(&game);
+    drop(terminal); // Leave the game screen so the results stay on the terminal afterwards.
 
-        result_string.push_str(&row_string);
-        i += 1;
+    let pack = game.get_pack();
+    let score = game.get_number_manager().get_score();
+    if victory {
+        execute!(stdout, Print(format!("You cleared {}! Score: {score}\n", pack.get_name())))?;
+    }
+    else {
+        execute!(stdout, Print(format!("Game over! Score: {score}\n")))?;
     }
 
-    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0), Print(result_string))?;
-    Ok(())
+    // The input thread is left waiting for a key press, which ends along with the program.
+    record_high_score(&mut stdout, pack, game.get_number_manager())
 }
 
-/// Checks for collisions between rucman and the ghosts and handles the cases for vulnerable and invulnerable ghosts.
-/// Returns a character if rucman collided with an invulnerable ghost.
-fn check_collision(rucman: &mut CharacterData, ghosts: &mut Vec<CharacterData>, score_manager: &mut NumberManager) -> Option<Character> {
-    for ghost in ghosts.iter_mut() {
-        if ghost.get_position() == rucman.get_position() {
-            match ghost.get_vulnerability() {
-                Vulnerability::Vulnerable => {
-                    score_manager.add_score(200);
-                    reset_character(ghost);
-                }
-                Vulnerability::Invulnerable => {
-                    score_manager.lose_life();
-                    return Some(ghost.get_character());
-                }
-            }
+/// Shows the title screen, where the greedy bot plays the pack as a demo until a key is pressed.
+/// Returns false if the player quit instead of starting a game.
+fn attract_mode(stdout: &mut Stdout, pack: &LevelPack, size: &Mutex<TerminalSize>, controls: &Controls, input_thread: &JoinHandle<()>) -> io::Result<bool> {
+    let frame_sleep = Duration::new(0, 125_000_000);
+    let mut demo = Game::new(pack.clone())?;
+    let mut bot = GreedyBot::default();
+    let mut camera = Camera::default();
+
+    while !controls.started.load(Ordering::Relaxed) {
+        if input_thread.is_finished() { return Ok(false); }
+
+        if let Some(direction) = bot.choose_direction(&demo) { demo.turn(direction); }
+        match demo.step() {
+            StepOutcome::Caught(_) => demo.respawn(),
+            StepOutcome::LevelComplete => { if !demo.next_level()? { demo = Game::new(pack.clone())?; } },
+            StepOutcome::Continue => {},
         }
+        if demo.is_over() { demo = Game::new(pack.clone())?; }
+
+        draw_frame(stdout, &demo, *lock(size), "DEMO - Press any key to play", &mut camera, controls.overlay.load(Ordering::Relaxed))?;
+        sleep(frame_sleep);
     }
 
-    None
+    Ok(true)
 }
 
-/// Resets the maze and characters to their initial state
-fn reset_game(grid: &mut Grid, rucman: &mut CharacterData, ghosts: &mut Vec<CharacterData>) {
-    *grid = Grid::new();
-    reset_characters(rucman, ghosts);
-}
+/// Saves the final score to the high score table and prints the pack's best scores.
+fn record_high_score(stdout: &mut Stdout, pack: &LevelPack, score_manager: &NumberManager) -> io::Result<()> {
+    let path = Path::new(HIGH_SCORE_FILE);
+    let mut high_scores = HighScores::load(path)?;
+    let entry = HighScore { score: score_manager.get_score(), level: score_manager.get_level(), pack: pack.get_name().to_string() };
+    if let Some(rank) = high_scores.add(entry) {
+        execute!(stdout, Print(format!("New high score! Rank #{rank}\n")))?;
+    }
+    high_scores.save(path)?;
 
-/// Resets all characters to their initial state
-fn reset_characters(rucman: &mut CharacterData, ghosts: &mut Vec<CharacterData>) {
-    reset_character(rucman);
-    for ghost in ghosts {
-        reset_character(ghost);
+    execute!(stdout, Print(format!("{} high scores:\n", pack.get_name())))?;
+    for (rank, entry) in high_scores.for_pack(pack.get_name()).take(5).enumerate() {
+        execute!(stdout, Print(format!("{}. {} (level {})\n", rank + 1, entry.score, entry.level)))?;
     }
+
+    Ok(())
+}
+
+/// Flags the input thread sets for the main loop.
+#[derive(Debug, Default)]
+struct Controls {
+    started: AtomicBool, // A key press left the title screen.
+    overlay: AtomicBool, // The debug overlay is shown.
+    console: Mutex<Console>, // The game is paused while it's open.
 }
 
-/// Resets a single character to their inital state
-fn reset_character(character: &mut CharacterData) {
-    *character = CharacterData::new(character.get_character());
+/// Locks state shared between the threads. A panic on the other thread while it held the lock doesn't stop the
+/// state from being used.
+fn lock<T>(shared: &Mutex<T>) -> MutexGuard<'_, T> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
 /// Creates a thread that handles user input.
 /// Directional key presses directly change the direction of Rucman.
+/// Resizing the terminal updates the size the next frame is laid out for.
+/// The first key press, other than quitting or toggling the debug overlay with F3, only leaves the title screen.
+/// The backtick key opens and closes the developer console, which takes every key press while it's open.
 /// Pressing Ctrl+C or Ctrl+Q closes the thread. The game should end if this thread ever closes.
-fn create_input_controller(game_manager: &EntityManager) -> JoinHandle<()> {
-    let rucman = game_manager.rucman.clone();
-    let grid = game_manager.grid.clone();
+fn create_input_controller(game: &Arc<Mutex<Game>>, size: &Arc<Mutex<TerminalSize>>, controls: &Arc<Controls>) -> JoinHandle<()> {
+    let game = game.clone();
+    let size = size.clone();
+    let controls = controls.clone();
 
     thread::spawn(move || loop {
         match read() {
             Ok(event) => {
                 match event {
-                    Event::Key(key) => {
-                        if !key.is_release() {
+                    Event::Key(key) if !key.is_release() => {
+                        let quit = matches!(key.code, KeyCode::Char('c') | KeyCode::Char('q')) && key.modifiers == KeyModifiers::CONTROL;
+                        if key.code == KeyCode::F(3) {
+                            controls.overlay.fetch_xor(true, Ordering::Relaxed);
+                            continue;
+                        }
+                        if !quit && !controls.started.swap(true, Ordering::Relaxed) { continue; }
+
+                        // While the console is open, keys type commands instead of steering.
+                        let mut console = lock(&controls.console);
+                        if key.code == KeyCode::Char('`') {
+                            console.toggle();
+                            continue;
+                        }
+                        if console.is_open() && !quit {
                             match key.code {
-                                // Directional inputs.
-                                KeyCode::Char('w') => rucman.lock().unwrap().set_direction_if_valid(Direction::up(), &grid.lock().unwrap()),
-                                KeyCode::Char('a') => rucman.lock().unwrap().set_direction_if_valid(Direction::left(), &grid.lock().unwrap()),
-                                KeyCode::Char('s') => rucman.lock().unwrap().set_direction_if_valid(Direction::down(), &grid.lock().unwrap()),
-                                KeyCode::Char('d') => rucman.lock().unwrap().set_direction_if_valid(Direction::right(), &grid.lock().unwrap()),
-                                
-                                // Control inputs.
-                                KeyCode::Char('c') | KeyCode::Char('q') => { // Quit
-                                    match key.modifiers {
-                                        KeyModifiers::CONTROL => break,
-                                        _ => {}
-                                    }
+                                KeyCode::Enter => {
+                                    drop(console); // The game is always locked before the console.
+                                    let mut game = lock(&game);
+                                    lock(&controls.console).submit(&mut game);
                                 },
-
-                                _ => {}, // Ignore all other keys.
+                                KeyCode::Esc => console.toggle(),
+                                KeyCode::Backspace => console.backspace(),
+                                KeyCode::Char(typed) => console.type_char(typed),
+                                _ => {},
                             }
+                            continue;
+                        }
+                        drop(console);
+
+                        match key.code {
+                            // Directional inputs.
+                            KeyCode::Char('w') => lock(&game).turn(Direction::up()),
+                            KeyCode::Char('a') => lock(&game).turn(Direction::left()),
+                            KeyCode::Char('s') => lock(&game).turn(Direction::down()),
+                            KeyCode::Char('d') => lock(&game).turn(Direction::right()),
+                            
+                            // Control inputs.
+                            KeyCode::Char('c') | KeyCode::Char('q') => { // Quit
+                                match key.modifiers {
+                                    KeyModifiers::CONTROL => break,
+                                    _ => {}
+                                }
+                            },
+
+                            _ => {}, // Ignore all other keys.
                         }
                     },
 
+                    Event::Resize(columns, rows) => *lock(&size) = (columns, rows),
+
                     _ => {} // Ignore all other events
                 }
             }
             Err(err) => {
-                let _ = execute!(stderr(), Print(format!("{err}")));
+                error!(%err, "Reading input failed"); // Printing it would draw over the game.
                 break;
             }
         }
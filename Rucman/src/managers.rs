@@ -1,14 +1,18 @@
-use std::sync::{Arc, Mutex};
-use crate::{Grid, CharacterData};
-
-/// Stores mutable pointers to the characters and maze.
-pub struct EntityManager {
-    pub grid: Arc<Mutex<Grid>>,
-    pub rucman: Arc<Mutex<CharacterData>>,
-    pub ghosts: Arc<Mutex<Vec<CharacterData>>>,
-}
+use crate::character::ElroyStage;
+use crate::events::{GameEvent, Subscriber};
+use crate::level::LevelOverrides;
+
+/// Points for eating a pellet.
+pub const PELLET_POINTS: u32 = 5;
+
+/// Points for eating a power pellet.
+pub const POWER_PELLET_POINTS: u32 = 10;
+
+/// Longest the scatter interval gets, in frames. 30 seconds.
+pub const MAX_SCATTER_INTERVAL: u128 = 120;
 
 /// Manages all numerical number.
+#[derive(Debug, Clone)]
 pub struct NumberManager {
     level: u32,
     score: u32,
@@ -17,6 +21,15 @@ pub struct NumberManager {
     scatter_interval: u128,
     vulnerability_length: u32,
     vulernability_timer: u32,
+    elroy_suspension_length: u32,
+    elroy_suspension_timer: u32,
+    elroy_thresholds: Option<(u32, u32)>,
+}
+
+impl Default for NumberManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NumberManager {
@@ -30,6 +43,9 @@ impl NumberManager {
             scatter_interval: 40,
             vulnerability_length: 28,
             vulernability_timer: 0,
+            elroy_suspension_length: 20,
+            elroy_suspension_timer: 0,
+            elroy_thresholds: None,
         }
     }
 
@@ -56,15 +72,21 @@ impl NumberManager {
     pub fn get_scatter_interval(&self) -> u128 {
         self.scatter_interval
     }
+
+    /// Retrieves how many frames the ghosts stay vulnerable after a power pellet on this level.
+    pub fn get_vulnerability_length(&self) -> u32 {
+        self.vulnerability_length
+    }
     
-    /// Adds provided points to score. Gives a life if one up score is achieved.
+    /// Adds provided points to score. Gives a life if one up score is achieved. Stops at the highest score
+    /// that can be counted rather than overflowing.
     pub fn add_score(&mut self, score: u32) {
-        if score <= 0 { return; }
+        if score == 0 { return; }
 
-        self.score += score;
-        if self.score >= self.one_up_score {
-            self.one_up_score *= 2;
-            self.lives += 1;
+        self.score = self.score.saturating_add(score);
+        if self.score >= self.one_up_score && self.one_up_score != u32::MAX {
+            self.one_up_score = self.one_up_score.saturating_mul(2);
+            self.lives = self.lives.saturating_add(1);
         }
     }
 
@@ -78,26 +100,53 @@ impl NumberManager {
         self.score -= score;
     }
 
+    /// Gives extra lives, stopping at the most that can be counted.
+    pub fn add_lives(&mut self, lives: u8) {
+        self.lives = self.lives.saturating_add(lives);
+    }
+
     /// Removes a life.
     pub fn lose_life(&mut self) {
         if self.lives == 0 { return; }
         
         self.lives -= 1;
         self.remove_score(150);
+        self.suspend_elroy();
     }
 
     /// Updates timers to new level.
     pub fn level_up(&mut self) {
-        self.level += 1;
+        self.advance_level();
         self.add_score(1000);
+    }
+
+    /// Updates timers to new level without the bonus for clearing one.
+    pub fn advance_level(&mut self) {
+        self.level = self.level.saturating_add(1);
+        self.elroy_suspension_timer = 0;
         self.shorten_vulnerability();
         self.lengthen_scatter_interval();
     }
 
+    /// Moves the level number on by the provided number of levels without touching the timers.
+    pub fn skip_levels(&mut self, levels: u32) {
+        self.level = self.level.saturating_add(levels);
+    }
+
+    /// Puts the level and its timers back to how they are at the start of a game, keeping the score and lives.
+    pub fn restart_levels(&mut self) {
+        let start = NumberManager::new();
+        self.level = start.level;
+        self.scatter_interval = start.scatter_interval;
+        self.vulnerability_length = start.vulnerability_length;
+        self.vulernability_timer = 0;
+        self.elroy_suspension_timer = 0;
+        self.elroy_thresholds = None;
+    }
+
     /// Shortens vulnerabilty window by 1 second and floors it at 2 seconds.
     pub fn shorten_vulnerability(&mut self) {
-        self.vulnerability_length -= 4;
-        if self.vulnerability_length < 8 { self.vulnerability_length = 8; } // Min at 2 seconds.
+        self.vulnerability_length = self.vulnerability_length.saturating_sub(4).max(8); // Min at 2 seconds.
     }
 
     /// Sets vulnerability timer to vulnerability length.
@@ -105,6 +154,11 @@ impl NumberManager {
         self.vulernability_timer = self.vulnerability_length;
     }
 
+    /// Sets vulnerability timer to the provided number of frames, however long vulnerability usually lasts.
+    pub fn set_vulnerability_timer(&mut self, frames: u32) {
+        self.vulernability_timer = frames;
+    }
+
     /// Lowers vulnerability timer by 1 frame.
     pub fn tick_vulernability_timer(&mut self) {
         if self.vulernability_timer == 0 {return;}
@@ -112,6 +166,11 @@ impl NumberManager {
         self.vulernability_timer -= 1;
     }
 
+    /// Retrieves how many frames the ghosts have left being vulnerable.
+    pub fn get_vulnerability_timer(&self) -> u32 {
+        self.vulernability_timer
+    }
+
     /// Returns true if vulnerability timer is 0.
     pub fn is_vulnerability_over(&self) -> bool {
         self.vulernability_timer == 0
@@ -119,7 +178,154 @@ impl NumberManager {
 
     /// Doubles the length of scatter interval. Maxes at 30 seconds.
     pub fn lengthen_scatter_interval(&mut self) {
-        self.scatter_interval *= 2;
-        if self.scatter_interval > 120 {self.scatter_interval = 120;} // Max out at 30 seconds.
+        self.scatter_interval = self.scatter_interval.saturating_mul(2).min(MAX_SCATTER_INTERVAL);
+    }
+
+    /// Applies a level's overrides. Overridden timers carry on through the usual progression on later levels
+    /// while overridden Elroy thresholds only last for the level.
+    pub fn apply_level_overrides(&mut self, overrides: &LevelOverrides) {
+        debug_assert!(overrides.scatter_interval.is_none_or(|interval| (1..=MAX_SCATTER_INTERVAL).contains(&interval)), "Level packs only allow scatter intervals from 1 to {MAX_SCATTER_INTERVAL}");
+        if let Some(scatter_interval) = overrides.scatter_interval { self.scatter_interval = scatter_interval; }
+        if let Some(vulnerability_length) = overrides.vulnerability_length { self.vulnerability_length = vulnerability_length; }
+        self.elroy_thresholds = overrides.elroy_thresholds;
+    }
+
+    /// Retrieves the pellets left thresholds for Elroy stages one and two on the current level.
+    pub fn get_elroy_thresholds(&self) -> (u32, u32) {
+        if let Some(thresholds) = self.elroy_thresholds { return thresholds; }
+
+        match self.level {
+            0..=1 => (20, 10),
+            2 => (30, 15),
+            3..=5 => (40, 20),
+            6..=8 => (50, 25),
+            9..=11 => (60, 30),
+            12..=14 => (80, 40),
+            15..=18 => (100, 50),
+            _ => (120, 60),
+        }
+    }
+
+    /// Determines Blinky's Elroy stage from the number of pellets left.
+    /// Elroy stays inactive while it is suspended after a lost life.
+    pub fn get_elroy_stage(&self, pellets_left: u32) -> ElroyStage {
+        if self.elroy_suspension_timer > 0 { return ElroyStage::Inactive; }
+
+        let (stage_one, stage_two) = self.get_elroy_thresholds();
+        if pellets_left < stage_two { ElroyStage::Two }
+        else if pellets_left < stage_one { ElroyStage::One }
+        else { ElroyStage::Inactive }
+    }
+
+    /// Suspends Elroy so a freshly respawned Rucman gets a head start. Lasts 5 seconds.
+    pub fn suspend_elroy(&mut self) {
+        self.elroy_suspension_timer = self.elroy_suspension_length;
+    }
+
+    /// Lowers Elroy suspension timer by 1 frame.
+    pub fn tick_elroy_suspension(&mut self) {
+        if self.elroy_suspension_timer == 0 { return; }
+
+        self.elroy_suspension_timer -= 1;
     }
-}
\ No newline at end of file
+}
+
+/// Keeps score: adds up the points of what Rucman eats and takes a life when he's caught.
+impl Subscriber for NumberManager {
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::PelletEaten { .. } => self.add_score(PELLET_POINTS),
+            GameEvent::PowerPelletEaten { .. } => self.add_score(POWER_PELLET_POINTS),
+            GameEvent::GhostEaten { points, .. } => self.add_score(points),
+            GameEvent::RucmanCaught { .. } => self.lose_life(),
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if Elroy stages start once pellets drop below the level's thresholds.
+    #[test]
+    fn test_elroy_stages() {
+        let number_manager = NumberManager::new();
+        assert_eq!(number_manager.get_elroy_stage(234), ElroyStage::Inactive);
+        assert_eq!(number_manager.get_elroy_stage(20), ElroyStage::Inactive);
+        assert_eq!(number_manager.get_elroy_stage(19), ElroyStage::One);
+        assert_eq!(number_manager.get_elroy_stage(10), ElroyStage::One);
+        assert_eq!(number_manager.get_elroy_stage(9), ElroyStage::Two);
+        assert_eq!(number_manager.get_elroy_stage(0), ElroyStage::Two);
+    }
+
+    /// Tests if Elroy thresholds rise with the level.
+    #[test]
+    fn test_elroy_thresholds_by_level() {
+        let mut number_manager = NumberManager::new();
+        assert_eq!(number_manager.get_elroy_thresholds(), (20, 10));
+        number_manager.level_up();
+        assert_eq!(number_manager.get_elroy_thresholds(), (30, 15));
+        assert_eq!(number_manager.get_elroy_stage(29), ElroyStage::One);
+    }
+
+    /// Tests if level overrides replace the level's timers and Elroy thresholds.
+    #[test]
+    fn test_level_overrides() {
+        let mut number_manager = NumberManager::new();
+        number_manager.apply_level_overrides(&LevelOverrides { scatter_interval: Some(60), vulnerability_length: None, elroy_thresholds: Some((15, 8)) });
+        assert_eq!(number_manager.get_scatter_interval(), 60);
+        assert_eq!(number_manager.get_elroy_stage(14), ElroyStage::One);
+        number_manager.apply_level_overrides(&LevelOverrides::default());
+        assert_eq!(number_manager.get_scatter_interval(), 60);
+        assert_eq!(number_manager.get_elroy_thresholds(), (20, 10));
+    }
+
+    /// Tests if a vulnerability length overridden below the floor is raised to it on the next level.
+    #[test]
+    fn test_short_vulnerability_override() {
+        let mut number_manager = NumberManager::new();
+        number_manager.apply_level_overrides(&LevelOverrides { scatter_interval: None, vulnerability_length: Some(3), elroy_thresholds: None });
+        number_manager.level_up();
+        number_manager.start_vulnerability_timer();
+        assert_eq!(number_manager.get_vulnerability_timer(), 8);
+    }
+
+    /// Tests if the scatter interval doubles up to its cap and stays there.
+    #[test]
+    fn test_scatter_interval_cap() {
+        let mut number_manager = NumberManager::new();
+        number_manager.level_up();
+        assert_eq!(number_manager.get_scatter_interval(), 80);
+        number_manager.level_up();
+        assert_eq!(number_manager.get_scatter_interval(), MAX_SCATTER_INTERVAL);
+        number_manager.apply_level_overrides(&LevelOverrides { scatter_interval: Some(MAX_SCATTER_INTERVAL), ..Default::default() });
+        number_manager.level_up();
+        assert_eq!(number_manager.get_scatter_interval(), MAX_SCATTER_INTERVAL);
+    }
+
+    /// Tests if losing a life suspends Elroy until the suspension timer runs out.
+    #[test]
+    fn test_elroy_suspended_after_lost_life() {
+        let mut number_manager = NumberManager::new();
+        number_manager.lose_life();
+        assert_eq!(number_manager.get_elroy_stage(5), ElroyStage::Inactive);
+        for _ in 0..20 {
+            number_manager.tick_elroy_suspension();
+        }
+        assert_eq!(number_manager.get_elroy_stage(5), ElroyStage::Two);
+    }
+
+    /// Tests if huge scores stop at the most that can be counted instead of overflowing.
+    #[test]
+    fn test_score_saturates() {
+        let mut number_manager = NumberManager::new();
+        number_manager.add_score(u32::MAX);
+        assert_eq!(number_manager.get_lives(), 4);
+        for _ in 0..40 {
+            number_manager.add_score(PELLET_POINTS);
+        }
+        assert_eq!(number_manager.get_score(), u32::MAX);
+        assert_eq!(number_manager.get_one_up_score(), u32::MAX);
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::character::Character;
+use crate::direction::Direction;
+use crate::error::{Result, RucmanError};
+use crate::grid::grid::{Grid, GridPoint, Portal};
+use crate::point::Vector2;
+
+/// Characters that draw a tile in a maze file, besides teleporter digits and spawns.
+pub const TILES: [char; 12] = ['.', '*', '█', '#', ' ', '-', '~', '_', '^', 'v', '<', '>'];
+
+/// A line of a maze file starting with `@`.
+enum Directive {
+    Scatter(Character, Vector2),
+    Spawn(Character, Vector2),
+    Portal { name: String, entrance: Vector2, destination: Vector2, two_way: bool, exit: Option<Direction> },
+}
+
+/// Parses the text of a maze file into a grid.
+///
+/// Rows are drawn with the same characters the maze is printed with and `#` also works as a wall. `-` is a
+/// door only ghosts can go through, `~` is a tunnel that slows ghosts down, `_` is a zone ghosts can't move up
+/// out of and `^`, `v`, `<` and `>` can only be crossed in the direction they point.
+/// A pair of matching digits marks two teleporters that lead to each other, and `R`, `B`, `P`, `I` and `C`
+/// mark where each character spawns. Lines starting with `;` are comments. Lines starting with `@` are
+/// directives:
+/// - `@scatter inky 25 25` chooses where a ghost scatters to. Ghosts without one scatter to the open space
+///   closest to their corner.
+/// - `@spawn rucman 13 20` chooses where a character spawns without drawing it, so it can start on a pellet,
+///   a teleporter or the same tile as another character.
+/// - `@portal <name> <x> <y> <-> <x> <y>` links two positions with teleporters that lead to each other.
+/// - `@portal <name> <x> <y> -> <x> <y> [direction]` is a one-way teleporter. Movers leave it facing the
+///   direction if there is one, otherwise they keep going the way they were.
+pub fn parse_maze(text: &str) -> Result<Grid> {
+    let mut rows = Vec::new();
+    let mut teleporters: HashMap<char, Vec<Vector2>> = HashMap::new();
+    let mut spawns: HashMap<Character, Vector2> = HashMap::new();
+    let mut scatter_positions: HashMap<Character, Vector2> = HashMap::new();
+    let mut portals = Vec::new();
+
+    for line in text.lines() {
+        if let Some(directive) = line.trim_end_matches('\r').strip_prefix('@') {
+            match parse_directive(directive)? {
+                Directive::Scatter(character, position) => { scatter_positions.insert(character, position); },
+                Directive::Spawn(character, position) => {
+                    if spawns.insert(character, position).is_some() {
+                        return Err(invalid(format!("{character} spawns more than once")));
+                    }
+                },
+                portal => portals.push(portal),
+            }
+        }
+    }
+
+    for line in grid_lines(text) {
+        let row_num = rows.len() as i32;
+        let mut row = Vec::new();
+        for (col_num, tile) in line.chars().enumerate() {
+            let pos = Vector2(col_num as i32, row_num);
+            match tile {
+                _ if TILES.contains(&tile) => row.push(tile),
+                '0'..='9' => {
+                    teleporters.entry(tile).or_default().push(pos);
+                    row.push(' ');
+                },
+                _ => {
+                    let character = Character::try_from(tile)
+                        .map_err(|tile| invalid(format!("Unknown tile '{tile}' at column {col_num}, row {row_num}")))?;
+                    if spawns.insert(character, pos).is_some() {
+                        return Err(invalid(format!("{character} spawns more than once")));
+                    }
+                    row.push(' ');
+                },
+            }
+        }
+        rows.push(row);
+    }
+
+    if rows.is_empty() { return Err(invalid("Maze has no rows".to_string())); }
+    let width = rows[0].len();
+    if let Some(row_num) = rows.iter().position(|row| row.len() != width) {
+        return Err(invalid(format!("Row {row_num} is {} wide but the maze is {width} wide", rows[row_num].len())));
+    }
+
+    let mut grid = Grid::try_from_rows(rows)?;
+
+    let mut teleporters: Vec<(char, Vec<Vector2>)> = teleporters.into_iter().collect();
+    teleporters.sort_by_key(|(digit, _)| *digit);
+    for (digit, ends) in teleporters {
+        if ends.len() != 2 {
+            return Err(invalid(format!("Teleporter {digit} needs exactly 2 ends but has {}", ends.len())));
+        }
+        grid.add_teleporter_pair(ends[0], ends[1])?;
+    }
+
+    let mut names = HashSet::new();
+    for portal in portals {
+        let Directive::Portal { name, entrance, destination, two_way, exit } = portal else { continue; };
+        if !names.insert(name.clone()) {
+            return Err(invalid(format!("Portal '{name}' is defined more than once")));
+        }
+
+        let ends = if two_way { vec![entrance, destination] } else { vec![entrance] };
+        for end in ends.iter().chain([&destination]) {
+            match grid.get_point(end) {
+                None => return Err(invalid(format!("Portal '{name}' has an end outside the maze at ({}, {})", end.0, end.1))),
+                Some(GridPoint::Teleporter(_)) if ends.contains(end) => return Err(invalid(format!("Portal '{name}' overlaps another teleporter at ({}, {})", end.0, end.1))),
+                Some(GridPoint::Wall) if ends.contains(end) => return Err(invalid(format!("Portal '{name}' starts in a wall at ({}, {})", end.0, end.1))),
+                _ => {},
+            }
+        }
+
+        let name: Option<Arc<str>> = Some(Arc::from(name));
+        if two_way {
+            grid.add_portal(entrance, Portal { destination, exit: None, name: name.clone() })?;
+            grid.add_portal(destination, Portal { destination: entrance, exit: None, name })?;
+        }
+        else {
+            grid.add_portal(entrance, Portal { destination, exit, name })?;
+        }
+    }
+
+    for character in Character::characters() {
+        match spawns.get(&character) {
+            Some(pos) if grid.get_point(pos).is_none() => return Err(invalid(format!("{character} spawns outside the maze at ({}, {})", pos.0, pos.1))),
+            Some(pos) => grid.set_spawn(character, *pos),
+            None => return Err(invalid(format!("{character} has no spawn"))),
+        }
+
+        if character == Character::Rucman { continue; }
+
+        let scatter_position = match scatter_positions.get(&character) {
+            Some(pos) => *pos,
+            None => {
+                let corner = scatter_corner(character, &grid);
+                grid.nearest_open_pos(corner).ok_or_else(|| invalid("Maze has no open spaces".to_string()))?
+            },
+        };
+        grid.set_scatter_position(character, scatter_position);
+    }
+
+    Ok(grid)
+}
+
+/// Writes a grid in the maze file format. Spawns on an empty tile are drawn and the rest, such as one on a
+/// pellet or sharing a tile, are written as directives so nothing underneath is lost. Every ghost's scatter
+/// position is written as a directive too. Named portals are written as portal directives under their name.
+/// The first 10 unnamed teleporter pairs are drawn as digits and the rest, along with unnamed one-way
+/// teleporters, are written as portal directives named `portal0`, `portal1` and so on.
+pub fn to_maze_text(grid: &Grid) -> String {
+    let mut rows: Vec<Vec<char>> = grid.get_maze().iter()
+        .map(|row| row.iter().map(|point| match point {
+            GridPoint::Wall => '#',
+            GridPoint::Tunnel => '~',
+            GridPoint::NoUp => '_',
+            _ => char::from(point.clone()),
+        }).collect())
+        .collect();
+
+    // Unnamed portals written as directives get a name no named portal has.
+    let taken: HashSet<Arc<str>> = grid.get_maze().iter().flatten()
+        .filter_map(|point| match point {
+            GridPoint::Teleporter(portal) => portal.name.clone(),
+            _ => None,
+        })
+        .collect();
+    let mut portal_count = 0;
+    let mut new_name = || loop {
+        let name = format!("portal{portal_count}");
+        portal_count += 1;
+        if !taken.contains(name.as_str()) { return name; }
+    };
+
+    let mut text = String::new();
+    let mut digits = '0'..='9';
+    for (row_num, row) in grid.get_maze().iter().enumerate() {
+        for (col_num, point) in row.iter().enumerate() {
+            let pos = Vector2(col_num as i32, row_num as i32);
+            let GridPoint::Teleporter(portal) = point else { continue; };
+            let dest = portal.destination;
+            let leads_back = portal.exit.is_none() && matches!(grid.get_point(&dest),
+                Some(GridPoint::Teleporter(other)) if other.destination == pos && other.exit.is_none() && other.name == portal.name);
+
+            if !leads_back {
+                let name = portal.name.as_deref().map(str::to_string).unwrap_or_else(&mut new_name);
+                let exit = portal.exit.map(|exit| format!(" {exit}")).unwrap_or_default();
+                text.push_str(&format!("@portal {name} {} {} -> {} {}{exit}\n", pos.0, pos.1, dest.0, dest.1));
+                continue;
+            }
+            if (dest.1, dest.0) < (pos.1, pos.0) { continue; } // Already written from the other end.
+
+            let name = match (&portal.name, digits.next()) {
+                (Some(name), _) => name.to_string(),
+                (None, Some(digit)) => {
+                    rows[pos.1 as usize][pos.0 as usize] = digit;
+                    rows[dest.1 as usize][dest.0 as usize] = digit;
+                    continue;
+                },
+                (None, None) => new_name(),
+            };
+            text.push_str(&format!("@portal {name} {} {} <-> {} {}\n", pos.0, pos.1, dest.0, dest.1));
+        }
+    }
+
+    for character in Character::characters() {
+        let spawn = grid.get_spawn(character);
+        let is_clear = grid.get_point(&spawn) == Some(GridPoint::Empty) && rows[spawn.1 as usize][spawn.0 as usize] == ' ';
+        if is_clear { rows[spawn.1 as usize][spawn.0 as usize] = char::from(character); }
+        else { text.push_str(&format!("@spawn {} {} {}\n", character.to_string().to_lowercase(), spawn.0, spawn.1)); }
+
+        if character == Character::Rucman { continue; }
+        let scatter_position = grid.get_scatter_position(character);
+        text.push_str(&format!("@scatter {} {} {}\n", character.to_string().to_lowercase(), scatter_position.0, scatter_position.1));
+    }
+
+    for row in rows {
+        text.extend(row);
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Retrieves the lines of a maze file that draw the maze, skipping blank lines, comments and directives.
+pub fn grid_lines(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty() && !line.starts_with(';') && !line.starts_with('@'))
+        .collect()
+}
+
+/// Retrieves the spawns given by a maze file's `@spawn` directives, skipping any directive that doesn't parse.
+pub fn spawn_directives(text: &str) -> Vec<(Character, Vector2)> {
+    text.lines()
+        .filter_map(|line| line.trim_end_matches('\r').strip_prefix('@'))
+        .filter_map(|directive| match parse_directive(directive) {
+            Ok(Directive::Spawn(character, position)) => Some((character, position)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parses the directives `scatter <ghost> <x> <y>`, `spawn <character> <x> <y>` and `portal <name> <x> <y> <-> or -> <x> <y> [direction]`.
+fn parse_directive(directive: &str) -> Result<Directive> {
+    let parts: Vec<&str> = directive.split_whitespace().collect();
+    match parts.as_slice() {
+        ["scatter", name, x, y] => {
+            let character = Character::from_name(name).ok_or_else(|| invalid(format!("Unknown ghost '{name}'")))?;
+            Ok(Directive::Scatter(character, parse_position(x, y)?))
+        },
+        ["spawn", name, x, y] => {
+            let character = Character::from_name(name).ok_or_else(|| invalid(format!("Unknown character '{name}'")))?;
+            Ok(Directive::Spawn(character, parse_position(x, y)?))
+        },
+        ["portal", name, x1, y1, arrow @ ("<->" | "->"), x2, y2, exit @ ..] => {
+            let two_way = *arrow == "<->";
+            let exit = match exit {
+                [] => None,
+                [direction] if !two_way => Some(Direction::from_name(direction).ok_or_else(|| invalid(format!("Unknown direction '{direction}'")))?),
+                _ => return Err(invalid(format!("Unknown directive '@{directive}'"))),
+            };
+            Ok(Directive::Portal { name: name.to_string(), entrance: parse_position(x1, y1)?, destination: parse_position(x2, y2)?, two_way, exit })
+        },
+        _ => Err(invalid(format!("Unknown directive '@{directive}'"))),
+    }
+}
+
+/// Parses a directive's x and y numbers.
+fn parse_position(x: &str, y: &str) -> Result<Vector2> {
+    let x = x.parse().map_err(|_| invalid(format!("Bad x '{x}'")))?;
+    let y = y.parse().map_err(|_| invalid(format!("Bad y '{y}'")))?;
+    Ok(Vector2(x, y))
+}
+
+/// The corner of the maze each ghost scatters towards.
+fn scatter_corner(character: Character, grid: &Grid) -> Vector2 {
+    let right = grid.get_width() as i32 - 1;
+    let bottom = grid.get_height() as i32 - 1;
+    match character {
+        Character::Blinky => Vector2(right, 0),
+        Character::Pinky | Character::Rucman => Vector2(0, 0),
+        Character::Inky => Vector2(right, bottom),
+        Character::Clyde => Vector2(0, bottom),
+    }
+}
+
+/// Creates a maze parse error with the provided message.
+fn invalid(message: String) -> RucmanError {
+    RucmanError::MazeParse(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if the bundled classic maze matches the built-in maze.
+    #[test]
+    fn classic_matches_builtin() {
+        let parsed = parse_maze(include_str!("../mazes/classic.maze")).unwrap();
+        let builtin = Grid::new();
+        assert_eq!(parsed.get_maze(), builtin.get_maze());
+        assert_eq!(parsed.pellets_left(), builtin.pellets_left());
+        for character in Character::characters() {
+            assert_eq!(parsed.get_spawn(character), builtin.get_spawn(character));
+            assert_eq!(parsed.get_scatter_position(character), builtin.get_scatter_position(character));
+        }
+    }
+
+    /// Tests if a written maze reads back the same.
+    #[test]
+    fn write_round_trip() {
+        let grid = Grid::new();
+        let text = to_maze_text(&grid);
+        assert!(text.starts_with("@scatter blinky 25 1\n"));
+        let parsed = parse_maze(&text).unwrap();
+        assert_eq!(parsed.get_maze(), grid.get_maze());
+        for character in Character::characters() {
+            assert_eq!(parsed.get_spawn(character), grid.get_spawn(character));
+        }
+    }
+
+    /// Tests if spawns on pellets, teleporters or each other are written as directives and read back the same.
+    #[test]
+    fn write_covered_spawns() {
+        let mut grid = Grid::new();
+        grid.set_spawn(Character::Rucman, Vector2(1, 1));
+        grid.set_spawn(Character::Inky, Vector2(0, 12));
+        grid.set_spawn(Character::Clyde, Vector2(13, 11));
+
+        let text = to_maze_text(&grid);
+        assert!(text.contains("@spawn rucman 1 1\n"));
+        assert!(text.contains("@spawn inky 0 12\n"));
+        assert!(text.contains("@spawn clyde 13 11\n")); // Pinky is drawn there.
+        let parsed = parse_maze(&text).unwrap();
+        assert_eq!(parsed.get_maze(), grid.get_maze());
+        assert_eq!(parsed.pellets_left(), grid.pellets_left());
+        for character in Character::characters() {
+            assert_eq!(parsed.get_spawn(character), grid.get_spawn(character));
+        }
+
+        assert!(parse_maze("@spawn rucman 1 1\n#######\n#RBPIC#\n").is_err()); // Drawn as well
+        assert!(parse_maze("@spawn clyde 9 9\n#######\n#RBPI.#\n").is_err()); // Outside the maze
+    }
+
+    /// Tests if spawns, teleporters and default scatter positions are read from the maze.
+    #[test]
+    fn parse_small_maze() {
+        let grid = parse_maze("; comment\n#####\n0.R.0\n#BPI#\n#*.C#\n#####\n").unwrap();
+        assert_eq!(grid.get_width(), 5);
+        assert_eq!(grid.get_height(), 5);
+        assert_eq!(grid.pellets_left(), 4);
+        assert_eq!(grid.get_spawn(Character::Rucman), Vector2(2, 1));
+        assert_eq!(grid.get_spawn(Character::Clyde), Vector2(3, 3));
+        assert_eq!(grid.get_maze()[1][0], GridPoint::Teleporter(Portal { destination: Vector2(4, 1), exit: None, name: None }));
+        assert_eq!(grid.get_maze()[1][4], GridPoint::Teleporter(Portal { destination: Vector2(0, 1), exit: None, name: None }));
+        assert_eq!(grid.get_scatter_position(Character::Blinky), Vector2(3, 1));
+        assert_eq!(grid.get_scatter_position(Character::Clyde), Vector2(1, 3));
+    }
+
+    /// Tests if special tiles are read and written.
+    #[test]
+    fn special_tiles() {
+        let text = "##########\n#R~_-^v<>#\n#BPIC....#\n##########\n";
+        let grid = parse_maze(text).unwrap();
+        assert_eq!(grid.get_maze()[1][2], GridPoint::Tunnel);
+        assert_eq!(grid.get_maze()[1][3], GridPoint::NoUp);
+        assert_eq!(grid.get_maze()[1][4], GridPoint::Door);
+        assert_eq!(grid.get_maze()[1][5], GridPoint::OneWay(Direction::up()));
+        assert_eq!(grid.get_maze()[1][8], GridPoint::OneWay(Direction::right()));
+        assert!(to_maze_text(&grid).contains("#R~_-^v<>#\n"));
+    }
+
+    /// Tests if malformed mazes are rejected instead of panicking.
+    #[test]
+    fn parse_errors() {
+        assert!(parse_maze("").is_err());
+        assert!(parse_maze("#####\n#RBx#\n#####\n").is_err()); // Unknown tile
+        assert!(parse_maze("#####\n#RBPI#\n#####\n").is_err()); // Missing Clyde
+        assert!(parse_maze("#######\n#RBPIC#\n#RBPIC#\n").is_err()); // Duplicate spawns
+        assert!(parse_maze("#######\n0RBPIC#\n#######\n").is_err()); // Unpaired teleporter
+        assert!(parse_maze("@warp 1 1\n#######\n#RBPIC#\n").is_err()); // Unknown directive
+        assert!(parse_maze("#######\n#RBPIC#\n######\n").is_err()); // Ragged rows
+        assert!(parse_maze("@portal a 1 1 -> 9 9\n#######\n#RBPIC#\n").is_err()); // Portal outside the maze
+        assert!(parse_maze("@portal a 0 0 -> 1 1\n#######\n#RBPIC#\n").is_err()); // Portal in a wall
+        assert!(parse_maze("@portal a 1 1 <-> 2 1 up\n#######\n#RBPIC#\n").is_err()); // Exit on a pair
+    }
+
+    /// Tests if named portals can be paired, one-way and have exit directions.
+    #[test]
+    fn parse_portals() {
+        let text = "@portal loop 0 1 <-> 6 1\n@portal drop 3 1 -> 5 3 down\n#######\n ..... \n#RBPIC#\n#.....#\n#######\n";
+        let grid = parse_maze(text).unwrap();
+        assert_eq!(grid.follow_portal(Vector2(0, 1), Direction::left()), (Vector2(6, 1), Direction::left()));
+        assert_eq!(grid.follow_portal(Vector2(6, 1), Direction::right()), (Vector2(0, 1), Direction::right()));
+        assert_eq!(grid.follow_portal(Vector2(3, 1), Direction::right()), (Vector2(5, 3), Direction::down()));
+        assert_eq!(grid.follow_portal(Vector2(5, 3), Direction::up()), (Vector2(5, 3), Direction::up()));
+        assert_eq!(grid.pellets_left(), 9);
+
+        assert!(parse_maze(&format!("@portal loop 1 3 -> 2 3\n{text}")).is_err()); // Duplicate name
+        assert!(parse_maze(&format!("@portal other 3 1 -> 2 3\n{text}")).is_err()); // Overlapping portals
+    }
+
+    /// Tests if portals that can't be drawn as digits are written as directives.
+    #[test]
+    fn write_portals() {
+        let mut grid = Grid::new();
+        grid.add_portal(Vector2(1, 1), Portal { destination: Vector2(25, 26), exit: Some(Direction::up()), name: None }).unwrap();
+        for row in [4, 7, 17, 20, 23, 26] {
+            grid.add_teleporter_pair(Vector2(2, row), Vector2(24, row)).unwrap();
+        }
+        for col in [3, 4, 5, 6] {
+            grid.add_teleporter_pair(Vector2(col, 4), Vector2(col, 26)).unwrap();
+        }
+
+        let text = to_maze_text(&grid);
+        assert!(text.contains("@portal portal0 1 1 -> 25 26 up\n"));
+        assert!(text.contains(" <-> "));
+        assert_eq!(to_maze_text(&parse_maze(&text).unwrap()), text); // Read back, the portals have the names they were written with.
+    }
+
+    /// Tests if named portals keep their names when written and unnamed ones don't take them.
+    #[test]
+    fn write_named_portals() {
+        let text = "@portal loop 0 1 <-> 6 1\n@portal portal0 3 1 -> 5 3 down\n#######\n ..... \n#RBPIC#\n#.....#\n#######\n";
+        let mut grid = parse_maze(text).unwrap();
+        grid.add_portal(Vector2(1, 3), Portal { destination: Vector2(2, 3), exit: None, name: None }).unwrap();
+
+        let written = to_maze_text(&grid);
+        assert!(written.contains("@portal loop 0 1 <-> 6 1\n"));
+        assert!(written.contains("@portal portal0 3 1 -> 5 3 down\n"));
+        assert!(written.contains("@portal portal1 1 3 -> 2 3\n"));
+        assert_eq!(to_maze_text(&parse_maze(&written).unwrap()), written);
+    }
+}
@@ -0,0 +1,206 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use std::ops::ControlFlow;
+
+use crate::bot::{Bot, GreedyBot};
+use crate::direction::Direction;
+use crate::distance::breadth_first;
+use crate::game::{Game, StepOutcome};
+use crate::grid::grid::Mover;
+
+/// How a Monte Carlo tree search spends its time each frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsConfig {
+    pub budget: u32, // Futures played out per frame.
+    pub frames_per_move: u32, // Frames a move in the tree plays out before the next choice.
+    pub rollout_frames: u32, // Frames the greedy bot plays after the tree runs out.
+    pub exploration: f64, // How much untried moves are favoured over ones that have done well.
+    pub death_penalty: f64, // Points a future loses for getting Rucman caught.
+    pub pellet_pull: f64, // Points a future loses per step it ends away from the nearest pellet, so far off pellets still draw Rucman in.
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig { budget: 100, frames_per_move: 2, rollout_frames: 16, exploration: 1.4, death_penalty: 500.0, pellet_pull: 5.0 }
+    }
+}
+
+/// A choice of move in the search tree and everything tried after it.
+#[derive(Debug)]
+struct Node {
+    direction: Direction,
+    visits: u32,
+    total: f64, // The value of every future played through here, added up.
+    children: Vec<Node>,
+    untried: Vec<Direction>,
+}
+
+impl Node {
+    /// Creates an unvisited node for the move, with every move from where it leads still to try.
+    fn new(direction: Direction, game: &Game) -> Self {
+        Node { direction, visits: 0, total: 0.0, children: Vec::new(), untried: moves(game) }
+    }
+
+    /// Retrieves the average value of the futures played through here.
+    fn mean(&self) -> f64 {
+        self.total / self.visits.max(1) as f64
+    }
+}
+
+/// Plays by searching the possible futures of every frame. Each future is played on a clone of the game, so
+/// it's exactly what would happen if the ghosts kept behaving the same way. Futures are scored by the points made
+/// along the way and how close they end to a pellet, or a penalty if Rucman gets caught. A strong but slow
+/// reference player.
+#[derive(Debug, Clone)]
+pub struct MctsBot {
+    config: MctsConfig,
+    rng: StdRng,
+}
+
+impl MctsBot {
+    /// Creates a bot that searches with the provided settings, trying moves in an order from the provided seed.
+    pub fn new(config: MctsConfig, seed: u64) -> Self {
+        MctsBot { config, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Plays a future from the root, growing the tree by a node.
+    fn iterate(&mut self, root: &mut Node, game: &Game) {
+        let mut future = game.clone();
+        let start_score = game.get_number_manager().get_score() as f64;
+        let mut path = Vec::new(); // Indexes of the children chosen at each level.
+        let mut caught = false;
+
+        // Select down the tree, then expand a move that hasn't been tried.
+        let mut node = &mut *root;
+        loop {
+            if !node.untried.is_empty() {
+                let direction = node.untried.swap_remove(self.rng.random_range(0..node.untried.len()));
+                caught = !self.play(&mut future, direction, self.config.frames_per_move);
+                node.children.push(Node::new(direction, &future));
+                path.push(node.children.len() - 1);
+                break;
+            }
+            if node.children.is_empty() { break; }
+
+            let index = self.select(node);
+            node = &mut node.children[index];
+            path.push(index);
+            if !self.play(&mut future, node.direction, self.config.frames_per_move) {
+                caught = true;
+                break;
+            }
+        }
+
+        // Play on with a quick greedy bot that only steers clear of ghosts right next to Rucman.
+        if !caught {
+            let mut rollout = GreedyBot::new(1);
+            for _ in 0..self.config.rollout_frames {
+                let direction = rollout.choose_direction(&future).unwrap_or(future.get_rucman().get_direction());
+                if !self.play(&mut future, direction, 1) {
+                    caught = true;
+                    break;
+                }
+            }
+        }
+
+        let value = future.get_number_manager().get_score() as f64 - start_score
+            - if caught { self.config.death_penalty } else { self.config.pellet_pull * pellet_distance(&future) as f64 };
+
+        // Back up the value along the path taken.
+        let mut node = root;
+        node.visits += 1;
+        node.total += value;
+        for index in path {
+            node = &mut node.children[index];
+            node.visits += 1;
+            node.total += value;
+        }
+    }
+
+    /// Chooses the child with the best upper confidence bound. Values are scaled by the death penalty so the
+    /// exploration setting means the same thing whatever the penalty is.
+    fn select(&self, node: &Node) -> usize {
+        let scale = self.config.death_penalty.abs().max(1.0);
+        let log_visits = (node.visits.max(1) as f64).ln();
+        let bound = |child: &Node| child.mean() / scale + self.config.exploration * (log_visits / child.visits.max(1) as f64).sqrt();
+
+        (0..node.children.len())
+            .max_by(|a, b| bound(&node.children[*a]).total_cmp(&bound(&node.children[*b])))
+            .unwrap_or(0)
+    }
+
+    /// Turns Rucman and plays out frames. Returns false if he was caught. Clearing the level ends the future early,
+    /// since the next maze isn't worth searching.
+    fn play(&self, game: &mut Game, direction: Direction, frames: u32) -> bool {
+        game.turn(direction);
+        for _ in 0..frames {
+            match game.step() {
+                StepOutcome::Caught(_) => return false,
+                StepOutcome::LevelComplete => return true,
+                StepOutcome::Continue => {},
+            }
+        }
+
+        true
+    }
+}
+
+impl Bot for MctsBot {
+    fn choose_direction(&mut self, game: &Game) -> Option<Direction> {
+        let mut root = Node::new(game.get_rucman().get_direction(), game);
+        if root.untried.len() == 1 { return root.untried.first().copied(); } // Nothing to decide.
+
+        for _ in 0..self.config.budget {
+            self.iterate(&mut root, game);
+        }
+
+        root.children.iter().max_by_key(|child| child.visits).map(|child| child.direction)
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new(MctsConfig::default(), 0)
+    }
+}
+
+/// Finds the moves worth trying from where Rucman is: every way he can go, or the way he's facing if he's stuck.
+fn moves(game: &Game) -> Vec<Direction> {
+    let rucman = game.get_rucman();
+    let res: Vec<Direction> = Direction::directions().into_iter()
+        .filter(|direction| game.get_grid().can_move(&rucman.get_position(), *direction, Mover::Rucman))
+        .collect();
+
+    if res.is_empty() { vec![rucman.get_direction()] } else { res }
+}
+
+/// Counts the steps from Rucman to the nearest pellet. Mazes without any left are 0 steps away.
+fn pellet_distance(game: &Game) -> u32 {
+    let grid = game.get_grid();
+    let start = game.get_rucman().get_position();
+    breadth_first(grid, &[start], Mover::Rucman, |pos, steps, _| {
+        if grid.has_pellet(&pos) { ControlFlow::Break(steps) } else { ControlFlow::Continue(true) }
+    }).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LevelPack;
+    use crate::simulate::simulate_game;
+
+    /// Tests if searching futures keeps Rucman alive better than the greedy bot while still eating, even with a
+    /// small budget.
+    #[test]
+    fn outlasts_greedy_bot() {
+        let config = MctsConfig { budget: 20, ..MctsConfig::default() };
+        let mcts = simulate_game(LevelPack::classic(), &mut MctsBot::new(config.clone(), 0), 0, 300).unwrap();
+        let greedy = simulate_game(LevelPack::classic(), &mut GreedyBot::default(), 0, 300).unwrap();
+        assert!(mcts.get_total_deaths() < greedy.get_total_deaths());
+        assert!(mcts.pellets_eaten > 50);
+
+        // The same seed searches the same way.
+        assert_eq!(mcts, simulate_game(LevelPack::classic(), &mut MctsBot::new(config, 0), 0, 300).unwrap());
+    }
+}
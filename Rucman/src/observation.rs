@@ -0,0 +1,203 @@
+use crate::character::{Character, Vulnerability};
+use crate::direction::Direction;
+use crate::game::Game;
+use crate::grid::grid::GridPoint;
+use crate::point::Vector2;
+
+/// The planes of an observation, in the order they're stored.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Channel {
+    Walls, // Walls and anything else outside the maze, such as padding.
+    Pellets,
+    PowerPellets,
+    Blinky,
+    Pinky,
+    Inky,
+    Clyde,
+    Frightened, // Every vulnerable ghost, on top of its own plane.
+    Rucman,
+    Direction, // 1 to 4 at Rucman's cell for up, down, left and right, the order of `Direction::directions`.
+}
+
+impl Channel {
+    /// Retrieves every channel in the order they're stored.
+    pub fn channels() -> [Self; CHANNELS] {
+        [
+            Channel::Walls,
+            Channel::Pellets,
+            Channel::PowerPellets,
+            Channel::Blinky,
+            Channel::Pinky,
+            Channel::Inky,
+            Channel::Clyde,
+            Channel::Frightened,
+            Channel::Rucman,
+            Channel::Direction,
+        ]
+    }
+
+    /// Retrieves the plane of the ghost's position.
+    pub fn of_ghost(ghost: Character) -> Option<Self> {
+        match ghost {
+            Character::Blinky => Some(Channel::Blinky),
+            Character::Pinky => Some(Channel::Pinky),
+            Character::Inky => Some(Channel::Inky),
+            Character::Clyde => Some(Channel::Clyde),
+            Character::Rucman => None,
+        }
+    }
+}
+
+/// How many planes an observation has.
+pub const CHANNELS: usize = 10;
+
+/// A numeric view of the game for learning agents, laid out as `[channel][row][column]` in one flat buffer, the
+/// same as a C-ordered array of shape `(CHANNELS, height, width)`. Cells are 0 or 1, except for the direction plane.
+///
+/// Observations can be bigger than the maze so every maze of a pack fits the same shape. The maze sits in the
+/// top left corner and the padding is wall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl Observation {
+    /// Encodes the game at the size of its maze.
+    pub fn encode(game: &Game) -> Self {
+        let grid = game.get_grid();
+        Self::encode_padded(game, grid.get_width(), grid.get_height())
+    }
+
+    /// Encodes the game into planes of the provided size. Anything of the maze past it is left out.
+    pub fn encode_padded(game: &Game, width: usize, height: usize) -> Self {
+        let mut res = Observation { width, height, data: vec![0; CHANNELS * width * height] };
+        res.data[..width * height].fill(1); // Everything starts as a wall until the maze is drawn in.
+
+        for (row, points) in game.get_grid().get_maze().iter().enumerate() {
+            for (col, point) in points.iter().enumerate() {
+                let pos = Vector2(col as i32, row as i32);
+                match point {
+                    GridPoint::Wall => {},
+                    GridPoint::Pellet => { res.set(Channel::Walls, pos, 0); res.set(Channel::Pellets, pos, 1); },
+                    GridPoint::PowerPellet => { res.set(Channel::Walls, pos, 0); res.set(Channel::PowerPellets, pos, 1); },
+                    _ => res.set(Channel::Walls, pos, 0),
+                }
+            }
+        }
+
+        for ghost in game.get_ghosts() {
+            let Some(channel) = Channel::of_ghost(ghost.get_character()) else { continue; };
+            res.set(channel, ghost.get_position(), 1);
+            if ghost.get_vulnerability() == Vulnerability::Vulnerable { res.set(Channel::Frightened, ghost.get_position(), 1); }
+        }
+
+        let rucman = game.get_rucman();
+        let direction = Direction::directions().iter().position(|direction| *direction == rucman.get_direction()).unwrap_or(0);
+        res.set(Channel::Rucman, rucman.get_position(), 1);
+        res.set(Channel::Direction, rucman.get_position(), direction as u8 + 1);
+
+        res
+    }
+
+    /// Retrieves the shape of the buffer as channels, rows then columns.
+    pub fn get_shape(&self) -> (usize, usize, usize) {
+        (CHANNELS, self.height, self.width)
+    }
+
+    /// Retrieves the flat buffer.
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Retrieves the buffer as floats, which is what most learning libraries take.
+    pub fn to_f32(&self) -> Vec<f32> {
+        self.data.iter().map(|value| *value as f32).collect()
+    }
+
+    /// Retrieves the value of a channel at the provided position. Positions outside the planes are 0.
+    pub fn get(&self, channel: Channel, pos: Vector2) -> u8 {
+        self.index(channel, pos).map_or(0, |index| self.data[index])
+    }
+
+    /// Decodes every position set in a channel, top to bottom then left to right.
+    pub fn positions(&self, channel: Channel) -> Vec<Vector2> {
+        let plane = channel as usize * self.width * self.height;
+        self.data[plane..plane + self.width * self.height].iter().enumerate()
+            .filter(|(_, value)| **value != 0)
+            .map(|(index, _)| Vector2((index % self.width) as i32, (index / self.width) as i32))
+            .collect()
+    }
+
+    /// Decodes the direction Rucman is facing.
+    pub fn rucman_direction(&self) -> Option<Direction> {
+        let pos = *self.positions(Channel::Direction).first()?;
+        Direction::directions().get(self.get(Channel::Direction, pos) as usize - 1).copied()
+    }
+
+    /// Sets the value of a channel at the provided position if it's inside the planes.
+    fn set(&mut self, channel: Channel, pos: Vector2, value: u8) {
+        if let Some(index) = self.index(channel, pos) { self.data[index] = value; }
+    }
+
+    /// Retrieves where in the buffer a channel's position is.
+    fn index(&self, channel: Channel, pos: Vector2) -> Option<usize> {
+        let (col, row) = (usize::try_from(pos.0).ok()?, usize::try_from(pos.1).ok()?);
+        if col >= self.width || row >= self.height { return None; }
+
+        Some((channel as usize * self.height + row) * self.width + col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::StepOutcome;
+    use crate::level::LevelPack;
+
+    /// Tests if decoding an observation gives back where everyone is, the pellets and the walls.
+    #[test]
+    fn decodes_positions() {
+        let mut game = Game::with_seed(LevelPack::classic(), 5).unwrap();
+        game.turn(Direction::left());
+        for _ in 0..20 {
+            if game.step() != StepOutcome::Continue { break; }
+        }
+
+        let observation = Observation::encode(&game);
+        let grid = game.get_grid();
+        assert_eq!(observation.get_shape(), (CHANNELS, grid.get_height(), grid.get_width()));
+        assert_eq!(observation.get_data().len(), CHANNELS * grid.get_height() * grid.get_width());
+
+        assert_eq!(observation.positions(Channel::Rucman), vec![game.get_rucman().get_position()]);
+        assert_eq!(observation.rucman_direction(), Some(game.get_rucman().get_direction()));
+        for ghost in game.get_ghosts() {
+            let channel = Channel::of_ghost(ghost.get_character()).unwrap();
+            assert_eq!(observation.positions(channel), vec![ghost.get_position()]);
+        }
+
+        let pellets = observation.positions(Channel::Pellets).len() + observation.positions(Channel::PowerPellets).len();
+        assert_eq!(pellets, grid.pellets_left() as usize);
+        for pos in observation.positions(Channel::Walls) {
+            assert_eq!(grid.get_point(&pos), Some(GridPoint::Wall));
+        }
+        assert!(observation.positions(Channel::Frightened).is_empty());
+    }
+
+    /// Tests if padded observations keep the maze in the corner, with wall everywhere else.
+    #[test]
+    fn padding() {
+        let game = Game::with_seed(LevelPack::classic(), 5).unwrap();
+        let grid = game.get_grid();
+        let observation = Observation::encode_padded(&game, 40, 40);
+        assert_eq!(observation.get_shape(), (CHANNELS, 40, 40));
+
+        let rucman = game.get_rucman().get_position();
+        assert_eq!(observation.get(Channel::Rucman, rucman), 1);
+        assert_eq!(observation.get(Channel::Walls, Vector2(39, 39)), 1);
+        assert_eq!(observation.get(Channel::Walls, Vector2(grid.get_width() as i32, 0)), 1);
+        assert_eq!(observation.positions(Channel::Pellets), Observation::encode(&game).positions(Channel::Pellets));
+        assert_eq!(observation.to_f32().len(), observation.get_data().len());
+    }
+}
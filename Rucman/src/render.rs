@@ -0,0 +1,497 @@
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::cursor;
+use crossterm::queue;
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+
+use std::io::{self, Write};
+
+use crate::camera::Camera;
+use crate::character::{Character, CharacterData};
+use crate::events::{GameEvent, Subscriber};
+use crate::game::Game;
+use crate::grid::grid::GridPoint;
+use crate::point::Vector2;
+
+/// The size of a terminal as columns then rows.
+pub type TerminalSize = (u16, u16);
+
+/// A character to draw along with its color, if it isn't the terminal's usual one.
+pub type StyledChar = (char, Option<Color>);
+
+/// The smallest view of the maze worth scrolling around, in cells on each side.
+const MIN_VIEW: u16 = 5;
+
+/// How many frames a message about an event stays up.
+const MESSAGE_FRAMES: u32 = 8;
+
+/// Where the stats go relative to the maze.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Layout {
+    Beside, // To the right of the maze's top rows, when the terminal is wide enough.
+    Below, // Under the maze, when the terminal is tall enough but not wide enough.
+    Scrolling, // Under a view of part of the maze that follows Rucman, when the maze doesn't fit at all.
+    TooSmall, // Not even a small view fits, so a message asking for a bigger terminal is shown instead.
+}
+
+/// Builds the rows of the maze with everyone drawn over it.
+pub fn render_maze(game: &Game) -> Vec<String> {
+    maze_tiles(game).into_iter().map(|row| row.into_iter().collect()).collect()
+}
+
+/// Builds the tiles of the maze with everyone drawn over them.
+fn maze_tiles(game: &Game) -> Vec<Vec<char>> {
+    let mut pass_one = Vec::new();
+
+    /* What we're doing here is like painting a landscape. We start with painting the background
+    and then we put the details and subjects over it.*/
+    // Collect the maze (the background)
+    for row in game.get_grid().get_maze() {
+        let mut row_collect = Vec::new();
+        for col in row {
+            row_collect.push(char::from(col));
+        }
+        pass_one.push(row_collect);
+    }
+
+    // Place the ghosts and rucman over the maze (the subjects)
+    let rucman = game.get_rucman();
+    let pos = rucman.get_position();
+    pass_one[pos.1 as usize][pos.0 as usize] = char::from(rucman);
+
+    // Ghosts second so they overlap Rucman so its obvious when rucman is dead.
+    for ghost in game.get_ghosts() {
+        let pos = ghost.get_position();
+        pass_one[pos.1 as usize][pos.0 as usize] = char::from(ghost);
+    }
+
+    pass_one
+}
+
+/// Builds the tiles of the maze with everyone drawn over them. The debug overlay draws each ghost's path and
+/// target in its color, with the ghost itself, so it's clear where they're all going.
+fn styled_tiles(game: &Game, overlay: bool) -> Vec<Vec<StyledChar>> {
+    let mut res: Vec<Vec<StyledChar>> = maze_tiles(game).into_iter()
+        .map(|row| row.into_iter().map(|tile| (tile, None)).collect())
+        .collect();
+    if !overlay { return res; }
+
+    let occupied: Vec<_> = game.get_ghosts().iter().chain([game.get_rucman()]).map(|character| character.get_position()).collect();
+    let mut mark = |pos: Vector2, tile: StyledChar| {
+        if occupied.contains(&pos) { return; }
+        if let Some(cell) = res.get_mut(pos.1 as usize).and_then(|row| row.get_mut(pos.0 as usize)) { *cell = tile; }
+    };
+    for ghost in game.get_ghosts() {
+        let color = Some(ghost_color(ghost.get_character()));
+        for pos in ghost.get_nav_path() {
+            mark(*pos, ('·', color));
+        }
+        if let Some(target) = ghost.get_target() { mark(target, ('×', color)); }
+    }
+
+    for ghost in game.get_ghosts() {
+        let pos = ghost.get_position();
+        res[pos.1 as usize][pos.0 as usize] = (char::from(ghost), Some(ghost_color(ghost.get_character())));
+    }
+
+    res
+}
+
+/// Retrieves the color a ghost is drawn in by the debug overlay.
+pub fn ghost_color(ghost: Character) -> Color {
+    match ghost {
+        Character::Blinky => Color::Red,
+        Character::Pinky => Color::Magenta,
+        Character::Inky => Color::Cyan,
+        Character::Clyde => Color::DarkYellow,
+        Character::Rucman => Color::Yellow,
+    }
+}
+
+/// Builds a map of the whole maze that fits in the provided width and height, with a dot for every ghost.
+/// Each character covers a square of cells. It shows Rucman if he's in the square, otherwise a ghost if there
+/// is one, otherwise shade if any of the cells are open.
+pub fn render_minimap(game: &Game, max_width: usize, max_height: usize) -> Vec<String> {
+    let grid = game.get_grid();
+    let (width, height) = (grid.get_width(), grid.get_height());
+    let scale = width.div_ceil(max_width.max(1)).max(height.div_ceil(max_height.max(1))).max(1);
+
+    let mut res = vec![vec![' '; width.div_ceil(scale)]; height.div_ceil(scale)];
+    for (row, points) in grid.get_maze().iter().enumerate() {
+        for (col, point) in points.iter().enumerate() {
+            if *point != GridPoint::Wall { res[row / scale][col / scale] = '░'; }
+        }
+    }
+
+    for ghost in game.get_ghosts() {
+        let pos = ghost.get_position();
+        res[pos.1 as usize / scale][pos.0 as usize / scale] = '•';
+    }
+    let pos = game.get_rucman().get_position();
+    res[pos.1 as usize / scale][pos.0 as usize / scale] = 'R';
+
+    res.into_iter().map(|row| row.into_iter().collect()).collect()
+}
+
+/// Builds the lines of stats shown alongside the maze, followed by the status message if there is one.
+pub fn render_hud(game: &Game, status: &str) -> Vec<String> {
+    let score_manager = game.get_number_manager();
+    let mut res = vec![
+        format!("Level: {}", score_manager.get_level()),
+        format!("Score: {}", score_manager.get_score()),
+        format!("Lives: {}", score_manager.get_lives()),
+        format!("One up at: {}", score_manager.get_one_up_score()),
+    ];
+    if !status.is_empty() { res.push(status.to_string()); }
+
+    res
+}
+
+/// Builds the debug overlay's lines of stats: the frame count, the timers and what every ghost is doing, with the
+/// ghosts in their colors.
+pub fn render_debug_hud(game: &Game) -> Vec<Vec<StyledChar>> {
+    let number_manager = game.get_number_manager();
+    // The ghosts scatter on every multiple of the interval and chase again once they reach their corner.
+    let mode_switch = number_manager.get_scatter_interval() - game.get_frames() % number_manager.get_scatter_interval();
+    let mut res = vec![
+        plain(&format!("Frame: {}", game.get_frames())),
+        plain(&format!("Mode switch in: {mode_switch}")),
+        plain(&format!("Frightened: {}", number_manager.get_vulnerability_timer())),
+    ];
+
+    let describe = |ghost: &CharacterData| match ghost.get_target() {
+        Some(target) => format!("{}: {:?} {:?} → {},{}", ghost.get_character(), ghost.get_ghost_mode(), ghost.get_vulnerability(), target.0, target.1),
+        None => format!("{}: {:?} {:?}", ghost.get_character(), ghost.get_ghost_mode(), ghost.get_vulnerability()),
+    };
+    for ghost in game.get_ghosts() {
+        res.push(describe(ghost).chars().map(|tile| (tile, Some(ghost_color(ghost.get_character())))).collect());
+    }
+
+    res
+}
+
+/// Turns text into characters drawn in the terminal's usual color.
+fn plain(line: &str) -> Vec<StyledChar> {
+    line.chars().map(|tile| (tile, None)).collect()
+}
+
+/// Chooses where the stats go for a maze and stats of the provided sizes in a terminal of the provided size.
+/// Stats beside the maze start on its second row, the same as they always have. Showing the whole maze is
+/// preferred over scrolling.
+pub fn choose_layout(maze: TerminalSize, hud: TerminalSize, terminal: TerminalSize) -> Layout {
+    let (maze_width, maze_height) = maze;
+    let (hud_width, hud_height) = hud;
+    let (columns, rows) = terminal;
+
+    if maze_width + 1 + hud_width <= columns && maze_height.max(hud_height + 1) <= rows { Layout::Beside }
+    else if maze_width.max(hud_width) <= columns && maze_height + hud_height <= rows { Layout::Below }
+    else if hud_width.max(MIN_VIEW) <= columns && hud_height + MIN_VIEW <= rows { Layout::Scrolling }
+    else { Layout::TooSmall }
+}
+
+/// Lays out a frame for a terminal of the provided size. Returns the line to draw on each row of the
+/// terminal, centered and starting from the top. The status is shown under the stats, such as who caught Rucman.
+/// The camera follows Rucman every frame, so it's ready whenever the maze stops fitting in the terminal.
+pub fn render_frame(game: &Game, terminal: TerminalSize, status: &str, camera: &mut Camera, overlay: bool) -> Vec<String> {
+    render_styled_frame(game, terminal, status, camera, overlay).into_iter()
+        .map(|line| line.into_iter().map(|(tile, _)| tile).collect())
+        .collect()
+}
+
+/// Lays out a frame the same as `render_frame`, keeping the colors of everything drawn in one.
+/// The debug overlay adds its stats under the usual ones.
+pub fn render_styled_frame(game: &Game, terminal: TerminalSize, status: &str, camera: &mut Camera, overlay: bool) -> Vec<Vec<StyledChar>> {
+    let tiles = styled_tiles(game, overlay);
+    let mut hud: Vec<Vec<StyledChar>> = render_hud(game, status).iter().map(|line| plain(line)).collect();
+    if overlay { hud.extend(render_debug_hud(game)); }
+
+    let width_of = |lines: &[Vec<StyledChar>]| lines.iter().map(|line| line.len()).max().unwrap_or(0) as u16;
+    let maze_size = (tiles.first().map_or(0, |row| row.len()) as u16, tiles.len() as u16);
+    let hud_size = (width_of(&hud), hud.len() as u16);
+    let layout = choose_layout(maze_size, hud_size, terminal);
+
+    let view = match layout {
+        Layout::Scrolling => (maze_size.0.min(terminal.0), maze_size.1.min(terminal.1 - hud_size.1)),
+        _ => maze_size,
+    };
+    let maze = (maze_size.0 as usize, maze_size.1 as usize);
+    camera.follow(game.get_rucman().get_position(), (view.0 as usize, view.1 as usize), maze);
+
+    let block: Vec<Vec<StyledChar>> = match layout {
+        Layout::Beside => {
+            let mut block = tiles;
+            for (index, line) in hud.into_iter().enumerate() {
+                match block.get_mut(index + 1) {
+                    Some(row) => { row.push((' ', None)); row.extend(line); },
+                    None => block.push(plain(&" ".repeat(maze_size.0 as usize + 1)).into_iter().chain(line).collect()),
+                }
+            }
+            block
+        },
+        Layout::Below => tiles.into_iter().chain(hud).collect(),
+        Layout::Scrolling => {
+            let origin = camera.get_origin();
+            let (left, top) = (origin.0 as usize, origin.1 as usize);
+            let mut shown: Vec<Vec<StyledChar>> = tiles[top..top + view.1 as usize].iter()
+                .map(|row| row[left..left + view.0 as usize].to_vec())
+                .collect();
+
+            // The minimap goes in the top right corner with a border, if there's room for it.
+            let minimap = render_minimap(game, view.0 as usize / 3, view.1 as usize / 3);
+            let minimap_width = minimap.first().map_or(0, |row| row.chars().count());
+            if minimap_width >= 3 && minimap.len() >= 3 {
+                let start = view.0 as usize - minimap_width - 1;
+                let bottom = format!("└{}", "─".repeat(minimap_width));
+                for (row, line) in minimap.iter().map(|line| format!("│{line}")).chain([bottom]).enumerate() {
+                    shown[row].splice(start.., plain(&line));
+                }
+            }
+
+            shown.into_iter().chain(hud).collect()
+        },
+        Layout::TooSmall => {
+            let needed = (hud_size.0.max(MIN_VIEW), hud_size.1 + MIN_VIEW);
+            let message = [String::from("Terminal too small!"), format!("Resize it to at least {}x{}.", needed.0, needed.1)];
+            return message.into_iter().map(|line| plain(&line).into_iter().take(terminal.0 as usize).collect()).take(terminal.1 as usize).collect();
+        },
+    };
+
+    // Center the block in the terminal.
+    let left = plain(&" ".repeat(terminal.0.saturating_sub(width_of(&block)) as usize / 2));
+    let top = terminal.1.saturating_sub(block.len() as u16) as usize / 2;
+    let mut res = vec![Vec::new(); top];
+    res.extend(block.into_iter().map(|line| left.iter().copied().chain(line).collect()));
+
+    res
+}
+
+/// Lays out a frame the same as `render_styled_frame` with lines of text under it, shrinking the space the frame
+/// has to make room for them.
+pub fn render_frame_with_footer(game: &Game, terminal: TerminalSize, status: &str, camera: &mut Camera, overlay: bool, footer: &[String]) -> Vec<Vec<StyledChar>> {
+    let height = terminal.1.saturating_sub(footer.len() as u16) as usize;
+    let mut res = render_styled_frame(game, (terminal.0, height as u16), status, camera, overlay);
+    res.resize(height, Vec::new());
+    res.extend(footer.iter().map(|line| plain(line).into_iter().take(terminal.0 as usize).collect::<Vec<_>>()));
+
+    res
+}
+
+/// Lays out the screen shown when Rucman clears the last level of a pack: the cleared maze with a box in the middle
+/// of the terminal congratulating him, along with the pack, the final score and the lives he had left.
+pub fn render_victory_screen(game: &Game, terminal: TerminalSize, camera: &mut Camera) -> Vec<Vec<StyledChar>> {
+    let number_manager = game.get_number_manager();
+    let text = [
+        String::from("YOU WIN!"),
+        format!("Cleared {}", game.get_pack().get_name()),
+        format!("Score: {}", number_manager.get_score()),
+        format!("Lives left: {}", number_manager.get_lives()),
+    ];
+    let inner = text.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 2;
+    let mut banner = vec![format!("┌{}┐", "─".repeat(inner))];
+    banner.extend(text.iter().map(|line| format!("│{line:^inner$}│")));
+    banner.push(format!("└{}┘", "─".repeat(inner)));
+
+    // The banner goes over the middle of the terminal, padding the frame out wherever it's too short.
+    let mut res = render_styled_frame(game, terminal, "", camera, false);
+    let (columns, rows) = (terminal.0 as usize, terminal.1 as usize);
+    let width = inner + 2;
+    let top = rows.saturating_sub(banner.len()) / 2;
+    let left = columns.saturating_sub(width) / 2;
+    res.resize(res.len().max(top + banner.len()), Vec::new());
+    for (row, line) in banner.iter().enumerate() {
+        let shown = &mut res[top + row];
+        if shown.len() < left + width { shown.resize(left + width, (' ', None)); }
+        shown.splice(left..left + width, line.chars().map(|tile| (tile, Some(Color::Yellow))));
+    }
+
+    res.into_iter().take(rows).map(|line| line.into_iter().take(columns).collect()).collect()
+}
+
+/// Turns game events into the status shown under the stats, such as who caught Rucman.
+#[derive(Debug, Default)]
+pub struct StatusMessages {
+    message: String,
+    frames_left: u32,
+}
+
+impl StatusMessages {
+    /// Creates an empty status.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieves the status to show, which is empty once the last message has been up long enough.
+    pub fn get_message(&self) -> &str {
+        if self.frames_left == 0 { "" } else { &self.message }
+    }
+
+    /// Counts down a frame of showing the message.
+    pub fn tick(&mut self) {
+        self.frames_left = self.frames_left.saturating_sub(1);
+    }
+}
+
+impl Subscriber for StatusMessages {
+    fn notify(&mut self, event: &GameEvent) {
+        self.message = match event {
+            GameEvent::RucmanCaught { by } => format!("Caught by: {by}"),
+            GameEvent::LevelCleared { .. } => String::from("Level complete!"),
+            GameEvent::GhostEaten { ghost, points } => format!("Ate {ghost}! +{points}"),
+            GameEvent::ExtraLife { .. } => String::from("Extra life!"),
+            _ => return,
+        };
+        self.frames_left = MESSAGE_FRAMES;
+    }
+}
+
+/// Clears the screen and draws a frame for a terminal of the provided size to the provided output, which doesn't
+/// have to be a terminal. Each line is moved to explicitly so nothing depends on how the terminal handles newlines.
+pub fn draw_frame(out: &mut impl Write, game: &Game, terminal: TerminalSize, status: &str, camera: &mut Camera, overlay: bool) -> io::Result<()> {
+    draw_lines(out, render_styled_frame(game, terminal, status, camera, overlay))
+}
+
+/// Clears the screen and draws the provided lines from the top of the output, in their colors.
+pub fn draw_lines(out: &mut impl Write, lines: Vec<Vec<StyledChar>>) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All))?;
+    for (row, line) in lines.into_iter().enumerate() {
+        if line.is_empty() { continue; }
+        queue!(out, cursor::MoveTo(0, row as u16))?;
+
+        // Print runs of the same color together.
+        for run in line.chunk_by(|a, b| a.1 == b.1) {
+            let text: String = run.iter().map(|(tile, _)| tile).collect();
+            match run[0].1 {
+                Some(color) => queue!(out, SetForegroundColor(color), Print(text), ResetColor)?,
+                None => queue!(out, Print(text))?,
+            }
+        }
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LevelPack;
+
+    /// Tests if a frame shows the maze, the characters and the stats beside the maze in a big terminal.
+    #[test]
+    fn frame_contents() {
+        let game = Game::new(LevelPack::classic()).unwrap();
+        let lines = render_frame(&game, (60, 28), "Caught by: Blinky", &mut Camera::default(), false);
+        assert_eq!(lines.len(), game.get_grid().get_height());
+        assert!(lines[1].ends_with(" Level: 1"));
+        assert!(lines[3].ends_with(" Lives: 3"));
+        assert!(lines[5].ends_with(" Caught by: Blinky"));
+        let left = lines[0].chars().take_while(|tile| *tile == ' ').count(); // Centered horizontally.
+        assert_eq!(lines[20].chars().nth(left + 13), Some('R'));
+        assert_eq!(lines[9].chars().nth(left + 13), Some('B'));
+
+        let mut buffer = Vec::new();
+        draw_frame(&mut buffer, &game, (60, 28), "", &mut Camera::default(), false).unwrap();
+        assert!(String::from_utf8(buffer).unwrap().contains(&lines[20]));
+    }
+
+    /// Tests if the stats move below the maze in narrow terminals, the maze scrolls in small ones and a message
+    /// is shown in tiny ones.
+    #[test]
+    fn layouts() {
+        assert_eq!(choose_layout((27, 31), (13, 4), (80, 40)), Layout::Beside);
+        assert_eq!(choose_layout((27, 31), (13, 4), (30, 40)), Layout::Below);
+        assert_eq!(choose_layout((27, 31), (13, 4), (80, 30)), Layout::Scrolling);
+        assert_eq!(choose_layout((27, 31), (13, 4), (20, 80)), Layout::Scrolling);
+        assert_eq!(choose_layout((27, 31), (13, 4), (12, 80)), Layout::TooSmall);
+        assert_eq!(choose_layout((27, 31), (13, 4), (80, 8)), Layout::TooSmall);
+
+        let game = Game::new(LevelPack::classic()).unwrap();
+        let lines = render_frame(&game, (30, 40), "", &mut Camera::default(), false);
+        assert_eq!(lines.len(), 4 + 28 + 4); // Centered vertically.
+        assert!(lines[4].starts_with(" █")); // Centered horizontally.
+        assert_eq!(lines[4 + 28].trim(), "Level: 1");
+
+        let lines = render_frame(&game, (12, 40), "", &mut Camera::default(), false);
+        assert_eq!(lines, vec!["Terminal too", "Resize it to"]);
+    }
+
+    /// Tests if a maze too big for the terminal is shown through a view around Rucman with a minimap.
+    #[test]
+    fn scrolling() {
+        let game = Game::new(LevelPack::classic()).unwrap();
+        let mut camera = Camera::default();
+        let lines = render_frame(&game, (40, 20), "", &mut camera, false);
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines[16].trim(), "Level: 1");
+        assert_eq!(camera.get_origin(), Vector2(0, 12)); // Rucman is at (13, 20) and the view is 16 rows tall.
+
+        let view = lines[..16].join("\n");
+        assert_eq!(view.matches('R').count(), 2); // In the view and on the minimap.
+        assert!(view.contains('•'));
+        assert!(lines[0].contains('│') && lines[5].contains('└'));
+    }
+
+    /// Tests if the debug overlay shows the ghosts' paths and targets in their colors along with the timers.
+    #[test]
+    fn debug_overlay() {
+        let mut game = Game::with_seed(LevelPack::classic(), 2).unwrap();
+        for _ in 0..3 {
+            game.step();
+        }
+
+        let blinky = game.get_ghosts().iter().find(|ghost| ghost.get_character() == Character::Blinky).unwrap().clone();
+        let target = blinky.get_target().unwrap();
+        let mut camera = Camera::default();
+        let lines = render_styled_frame(&game, (80, 40), "", &mut camera, true);
+        let left = lines.iter().find(|line| !line.is_empty()).unwrap().iter().take_while(|(tile, _)| *tile == ' ').count();
+        let top = lines.iter().take_while(|line| line.is_empty()).count();
+        let at = |pos: Vector2| lines[top + pos.1 as usize][left + pos.0 as usize];
+
+        assert_eq!(at(target), ('×', Some(Color::Red)));
+        assert_eq!(at(blinky.get_position()), ('B', Some(Color::Red)));
+        let text: Vec<String> = lines.iter().map(|line| line.iter().map(|(tile, _)| tile).collect()).collect();
+        assert!(text.iter().any(|line| line.ends_with("Frame: 3")));
+        assert!(text.iter().any(|line| line.ends_with("Mode switch in: 37")));
+        assert!(text.iter().any(|line| line.contains(&format!("Blinky: Scatter Invulnerable → {},{}", target.0, target.1))));
+
+        // Without the overlay nothing is colored.
+        let plain = render_styled_frame(&game, (80, 40), "", &mut camera, false);
+        assert!(plain.iter().flatten().all(|(_, color)| color.is_none()));
+    }
+
+    /// Tests if the victory screen puts its banner over the middle of the terminal, even one smaller than the maze.
+    #[test]
+    fn victory_screen() {
+        let game = Game::with_seed(LevelPack::classic(), 2).unwrap();
+        let lines = render_victory_screen(&game, (60, 31), &mut Camera::default());
+        let text: Vec<String> = lines.iter().map(|line| line.iter().map(|(tile, _)| tile).collect()).collect();
+        assert!(text.len() <= 31);
+        let banner = text.iter().position(|line| line.contains("YOU WIN!")).unwrap();
+        assert_eq!(banner, 13); // The 6 rows of the banner are centered.
+        assert!(text[banner - 1].ends_with("┌─────────────────┐"));
+        assert!(text[banner].ends_with("│    YOU WIN!     │"));
+        assert!(text[banner + 1].ends_with("│ Cleared Classic │"));
+        assert!(text[banner + 2].ends_with("│    Score: 0     │"));
+        assert!(text[banner + 3].ends_with("│  Lives left: 3  │"));
+        assert_eq!(lines[banner][20], ('│', Some(Color::Yellow)));
+
+        let lines = render_victory_screen(&game, (16, 4), &mut Camera::default());
+        assert_eq!(lines.len(), 4);
+        assert!(lines.iter().all(|line| line.len() <= 16));
+    }
+
+    /// Tests if events show a message for a few frames and other events leave it be.
+    #[test]
+    fn status_messages() {
+        let mut status = StatusMessages::new();
+        status.notify(&GameEvent::RucmanCaught { by: Character::Blinky });
+        status.notify(&GameEvent::PelletEaten { position: Vector2(1, 1) });
+        assert_eq!(status.get_message(), "Caught by: Blinky");
+
+        for _ in 0..MESSAGE_FRAMES {
+            status.tick();
+        }
+        assert_eq!(status.get_message(), "");
+
+        status.notify(&GameEvent::GhostEaten { ghost: Character::Inky, points: 200 });
+        assert_eq!(status.get_message(), "Ate Inky! +200");
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+/// Where high scores are saved.
+pub const HIGH_SCORE_FILE: &str = "highscores.txt";
+
+/// How many scores are kept for each level pack.
+const SCORES_PER_PACK: usize = 10;
+
+/// A single finished game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    pub level: u32,
+    pub pack: String,
+}
+
+/// The best scores of every level pack, best first.
+#[derive(Debug, Default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Loads high scores from disk. A missing file is an empty table.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parses tab separated `score level pack` lines, skipping any that are malformed.
+    pub fn parse(text: &str) -> Self {
+        let mut res = HighScores::default();
+        for line in text.lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(score), Some(level), Some(pack)) = (parts.next(), parts.next(), parts.next()) else { continue; };
+            let (Ok(score), Ok(level)) = (score.parse(), level.parse()) else { continue; };
+            res.add(HighScore { score, level, pack: pack.to_string() });
+        }
+
+        res
+    }
+
+    /// Saves high scores to disk.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&format!("{}\t{}\t{}\n", entry.score, entry.level, entry.pack));
+        }
+        fs::write(path, text)
+    }
+
+    /// Adds a score to the table, dropping the worst score of its pack if the pack is full.
+    /// Returns the entry's rank in its pack starting at 1, or None if it didn't make the table.
+    pub fn add(&mut self, entry: HighScore) -> Option<usize> {
+        let index = self.entries.iter().position(|other| other.score < entry.score).unwrap_or(self.entries.len());
+        let rank = self.entries[..index].iter().filter(|other| other.pack == entry.pack).count() + 1;
+        let pack = entry.pack.clone();
+        self.entries.insert(index, entry);
+
+        if self.for_pack(&pack).count() > SCORES_PER_PACK {
+            let last = self.entries.iter().rposition(|other| other.pack == pack).unwrap(); // The pack has entries.
+            self.entries.remove(last);
+        }
+
+        if rank <= SCORES_PER_PACK { Some(rank) } else { None }
+    }
+
+    /// Iterates through the scores of the provided pack, best first.
+    pub fn for_pack<'a>(&'a self, pack: &'a str) -> impl Iterator<Item = &'a HighScore> + 'a {
+        self.entries.iter().filter(move |entry| entry.pack == pack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, pack: &str) -> HighScore {
+        HighScore { score, level: 1, pack: pack.to_string() }
+    }
+
+    /// Tests if scores are ranked within their own pack.
+    #[test]
+    fn rank_by_pack() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.add(entry(100, "Classic")), Some(1));
+        assert_eq!(scores.add(entry(500, "Arcade")), Some(1));
+        assert_eq!(scores.add(entry(300, "Classic")), Some(1));
+        assert_eq!(scores.add(entry(200, "Classic")), Some(2));
+        let classic: Vec<u32> = scores.for_pack("Classic").map(|entry| entry.score).collect();
+        assert_eq!(classic, vec![300, 200, 100]);
+    }
+
+    /// Tests if only the best scores of each pack are kept.
+    #[test]
+    fn pack_table_is_capped() {
+        let mut scores = HighScores::default();
+        for score in 1..=SCORES_PER_PACK as u32 {
+            scores.add(entry(score * 10, "Classic"));
+        }
+        assert_eq!(scores.add(entry(5, "Classic")), None);
+        assert_eq!(scores.add(entry(1000, "Classic")), Some(1));
+        assert_eq!(scores.for_pack("Classic").count(), SCORES_PER_PACK);
+        assert_eq!(scores.for_pack("Classic").last().unwrap().score, 20);
+    }
+
+    /// Tests if saved scores can be read back.
+    #[test]
+    fn round_trip() {
+        let mut scores = HighScores::default();
+        scores.add(HighScore { score: 1200, level: 3, pack: String::from("Arcade") });
+        scores.add(entry(40, "Classic"));
+        let path = std::env::temp_dir().join(format!("rucman-scores-{}.txt", std::process::id()));
+        scores.save(&path).unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.entries, scores.entries);
+    }
+}
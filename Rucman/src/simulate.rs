@@ -0,0 +1,183 @@
+use rayon::prelude::*;
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::bot::{bot_by_name, Bot, BOT_NAMES};
+use crate::character::Character;
+use crate::error::{Result, RucmanError};
+use crate::events::{EventBus, EventLogger, GameEvent, Subscriber};
+use crate::game::{Game, StepOutcome};
+use crate::level::LevelPack;
+
+/// The ghosts in the order their deaths are counted in.
+pub const GHOSTS: [Character; 4] = [Character::Blinky, Character::Pinky, Character::Inky, Character::Clyde];
+
+/// What to simulate. Game n is played with the seed `seed + n`, for both the game and the bot.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub games: u32,
+    pub seed: u64,
+    pub bot: String,
+    pub max_ticks: u64, // Games still going after this many frames are cut short.
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig { games: 100, seed: 0, bot: String::from("random"), max_ticks: 100_000 }
+    }
+}
+
+/// How a single simulated game went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSummary {
+    pub seed: u64,
+    pub score: u32,
+    pub level: u32, // The level the game ended on.
+    pub deaths: [u32; 4], // Lives lost to each ghost, in the order of `GHOSTS`.
+    pub pellets_eaten: u32, // Pellets and power pellets, across every level.
+    pub pellets_per_life: f64, // Counts the life Rucman was still on if the game didn't end in a game over.
+    pub ticks: u64,
+    pub victory: bool, // Rucman cleared every level of the pack.
+}
+
+impl GameSummary {
+    /// Retrieves the total lives lost.
+    pub fn get_total_deaths(&self) -> u32 {
+        self.deaths.iter().sum()
+    }
+}
+
+/// Counts the pellets eaten and who caught Rucman as the game goes.
+impl Subscriber for GameSummary {
+    fn notify(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PelletEaten { .. } | GameEvent::PowerPelletEaten { .. } => self.pellets_eaten += 1,
+            GameEvent::RucmanCaught { by } => {
+                if let Some(index) = GHOSTS.iter().position(|ghost| ghost == by) { self.deaths[index] += 1; }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Plays a whole game with the provided bot without drawing or waiting between frames.
+pub fn simulate_game(pack: LevelPack, bot: &mut dyn Bot, seed: u64, max_ticks: u64) -> Result<GameSummary> {
+    let mut game = Game::with_seed(pack, seed)?;
+    let stats = Arc::new(Mutex::new(GameSummary { seed, score: 0, level: 1, deaths: [0; 4], pellets_eaten: 0, pellets_per_life: 0.0, ticks: 0, victory: false }));
+    let mut bus = EventBus::new();
+    bus.subscribe(stats.clone());
+    bus.subscribe(EventLogger);
+
+    let (mut ticks, mut victory) = (0, false);
+    while !game.is_over() && ticks < max_ticks {
+        if let Some(direction) = bot.choose_direction(&game) { game.turn(direction); }
+
+        let outcome = game.step();
+        game.publish_events(&mut bus);
+        ticks += 1;
+
+        match outcome {
+            StepOutcome::Caught(_) => game.respawn(),
+            StepOutcome::LevelComplete => {
+                victory = !game.next_level()?;
+                game.publish_events(&mut bus);
+                if victory { break; }
+            },
+            StepOutcome::Continue => {},
+        }
+    }
+
+    let mut summary = stats.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    summary.ticks = ticks;
+    summary.victory = victory;
+    let number_manager = game.get_number_manager();
+    summary.score = number_manager.get_score();
+    summary.level = number_manager.get_level();
+    let lives_played = summary.get_total_deaths() + !game.is_over() as u32;
+    summary.pellets_per_life = summary.pellets_eaten as f64 / lives_played.max(1) as f64;
+
+    Ok(summary)
+}
+
+/// Plays every game of the simulation in parallel across the machine's cores. Summaries are in seed order.
+pub fn simulate(pack: &LevelPack, config: &SimulationConfig) -> Result<Vec<GameSummary>> {
+    if bot_by_name(&config.bot, 0).is_none() {
+        return Err(RucmanError::InvalidConfig(format!("Unknown bot '{}', expected one of: {}", config.bot, BOT_NAMES.join(", "))));
+    }
+
+    (0..config.games as u64).into_par_iter()
+        .map(|game| {
+            let seed = config.seed.wrapping_add(game);
+            let mut bot = bot_by_name(&config.bot, seed).expect("Bot name was checked");
+            simulate_game(pack.clone(), bot.as_mut(), seed, config.max_ticks)
+        })
+        .collect()
+}
+
+/// Writes the summaries as CSV with a header row.
+pub fn to_csv(summaries: &[GameSummary]) -> String {
+    let mut res = String::from("seed,score,level,deaths,blinky,pinky,inky,clyde,pellets_eaten,pellets_per_life,ticks,victory\n");
+    for summary in summaries {
+        let [blinky, pinky, inky, clyde] = summary.deaths;
+        res.push_str(&format!(
+            "{},{},{},{},{blinky},{pinky},{inky},{clyde},{},{:.2},{},{}\n",
+            summary.seed, summary.score, summary.level, summary.get_total_deaths(),
+            summary.pellets_eaten, summary.pellets_per_life, summary.ticks, summary.victory,
+        ));
+    }
+
+    res
+}
+
+/// Writes the summaries as a JSON array with an object per game.
+pub fn to_json(summaries: &[GameSummary]) -> String {
+    let games: Vec<String> = summaries.iter().map(|summary| {
+        let deaths: Vec<String> = GHOSTS.iter().zip(summary.deaths)
+            .map(|(ghost, deaths)| format!("\"{}\":{deaths}", ghost.to_string().to_lowercase()))
+            .collect();
+        format!(
+            "  {{\"seed\":{},\"score\":{},\"level\":{},\"deaths\":{{{}}},\"pellets_eaten\":{},\"pellets_per_life\":{:.2},\"ticks\":{},\"victory\":{}}}",
+            summary.seed, summary.score, summary.level, deaths.join(","),
+            summary.pellets_eaten, summary.pellets_per_life, summary.ticks, summary.victory,
+        )
+    }).collect();
+
+    if games.is_empty() { String::from("[]\n") }
+    else { format!("[\n{}\n]\n", games.join(",\n")) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if a simulation is repeatable and its games end the way the summaries say.
+    #[test]
+    fn repeatable_games() {
+        let config = SimulationConfig { games: 4, seed: 10, bot: String::from("random"), max_ticks: 5_000 };
+        let summaries = simulate(&LevelPack::classic(), &config).unwrap();
+        assert_eq!(summaries, simulate(&LevelPack::classic(), &config).unwrap());
+        assert_eq!(summaries.iter().map(|summary| summary.seed).collect::<Vec<_>>(), vec![10, 11, 12, 13]);
+
+        for summary in &summaries {
+            assert!(summary.ticks <= 5_000);
+            assert!(summary.ticks == 5_000 || summary.victory || summary.get_total_deaths() >= 3);
+            assert!(summary.pellets_per_life <= summary.pellets_eaten as f64);
+        }
+
+        let config = SimulationConfig { bot: String::from("nobody"), ..config };
+        assert!(simulate(&LevelPack::classic(), &config).is_err());
+    }
+
+    /// Tests if summaries are written out with a row or object per game.
+    #[test]
+    fn output_formats() {
+        let summary = GameSummary { seed: 3, score: 450, level: 2, deaths: [1, 0, 2, 0], pellets_eaten: 90, pellets_per_life: 30.0, ticks: 800, victory: false };
+        let csv = to_csv(std::slice::from_ref(&summary));
+        assert_eq!(csv.lines().nth(1), Some("3,450,2,3,1,0,2,0,90,30.00,800,false"));
+
+        let json = to_json(&[summary.clone(), summary]);
+        assert!(json.contains("\"deaths\":{\"blinky\":1,\"pinky\":0,\"inky\":2,\"clyde\":0}"));
+        assert_eq!(json.matches("\"seed\":3").count(), 2);
+        assert_eq!(to_json(&[]), "[]\n");
+    }
+}
@@ -0,0 +1,111 @@
+use crossterm::cursor;
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::{self, stdout};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the details of the last crash are written.
+pub const CRASH_REPORT_FILE: &str = "crash.txt";
+
+/// Whether the terminal is currently in raw mode on the alternate screen.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Makes sure the panic hook is only installed once, however many guards are made.
+static HOOK: Once = Once::new();
+
+/// Puts the terminal in raw mode on an alternate screen with the cursor hidden, and puts it back the way it
+/// was when dropped. A panic hook puts it back too, so a crash never leaves the terminal unusable.
+#[derive(Debug)]
+pub struct TerminalGuard {
+    _private: (), // Only made through enter.
+}
+
+impl TerminalGuard {
+    /// Takes over the terminal until the guard is dropped.
+    pub fn enter() -> io::Result<Self> {
+        HOOK.call_once(install_panic_hook);
+
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(TerminalGuard { _private: () })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts the terminal back the way it was if a guard took it over. Does nothing if it's already been put back.
+pub fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) { return; }
+
+    // Nothing more can be done if these fail, and this may already be running because of a panic.
+    let _ = execute!(stdout(), cursor::Show, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+}
+
+/// Adds to the panic hook so the terminal is put back before the panic message prints, then writes a crash report.
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+
+        match fs::write(CRASH_REPORT_FILE, crash_report(info)) {
+            Ok(()) => eprintln!("A crash report was written to {CRASH_REPORT_FILE}."),
+            Err(err) => eprintln!("Couldn't write a crash report: {err}"),
+        }
+    }));
+}
+
+/// Builds the text of a crash report from a panic.
+fn crash_report(info: &PanicHookInfo) -> String {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or_default();
+    let location = info.location().map(|location| location.to_string()).unwrap_or_else(|| String::from("unknown"));
+
+    format!(
+        "Rucman {} crashed.\nTime: {time} seconds since the Unix epoch\nThread: {}\nMessage: {}\nLocation: {location}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        thread::current().name().unwrap_or("unnamed"),
+        panic_message(info.payload()),
+        Backtrace::force_capture(),
+    )
+}
+
+/// Retrieves the message a panic was given. Panics made with a message carry a str or a String.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() { message }
+    else if let Some(message) = payload.downcast_ref::<String>() { message }
+    else { "Unknown panic" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests if panic messages are found whether they were made from a str or a formatted String.
+    #[test]
+    fn panic_messages() {
+        let payload = panic::catch_unwind(|| panic!("Cannot convert x to a grid point")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "Cannot convert x to a grid point");
+
+        let tile = 'x';
+        let payload = panic::catch_unwind(|| panic!("Cannot convert {tile} to a grid point")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "Cannot convert x to a grid point");
+
+        let payload = panic::catch_unwind(|| panic::panic_any(5)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "Unknown panic");
+    }
+}
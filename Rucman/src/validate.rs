@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::character::Character;
+use crate::direction::Direction;
+use crate::grid::grid::{Grid, GridPoint, Mover};
+use crate::maze;
+use crate::point::Vector2;
+
+/// Denotes how serious a maze problem is. Errors make a maze unplayable, warnings are just worth a look.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A single problem found in a maze.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub position: Option<Vector2>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(position: Option<Vector2>, message: String) -> Self {
+        Diagnostic { severity: Severity::Error, position, message }
+    }
+
+    pub(crate) fn warning(position: Option<Vector2>, message: String) -> Self {
+        Diagnostic { severity: Severity::Warning, position, message }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match self.position {
+            Some(pos) => write!(f, "{severity} at ({}, {}): {}", pos.0, pos.1, self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+/// Returns true if any of the diagnostics is an error.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+/// Reads and validates a maze file.
+pub fn validate_maze_file(path: &Path) -> io::Result<Vec<Diagnostic>> {
+    Ok(validate_maze_text(&fs::read_to_string(path)?))
+}
+
+/// Validates the text of a maze file. Every problem the text has is reported instead of only the first,
+/// and once the text is well formed the maze it describes is checked with `validate_grid`.
+pub fn validate_maze_text(text: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let rows = maze::grid_lines(text);
+    if rows.is_empty() {
+        diagnostics.push(Diagnostic::error(None, String::from("Maze has no rows")));
+        return diagnostics;
+    }
+
+    let width = rows[0].chars().count();
+    let mut teleporters: HashMap<char, Vec<Vector2>> = HashMap::new();
+    let mut spawns: HashMap<Character, Vec<Vector2>> = HashMap::new();
+    for (character, pos) in maze::spawn_directives(text) {
+        spawns.entry(character).or_default().push(pos);
+    }
+    for (row_num, row) in rows.iter().enumerate() {
+        let row_width = row.chars().count();
+        if row_width != width {
+            diagnostics.push(Diagnostic::error(Some(Vector2(0, row_num as i32)), format!("Row is {row_width} wide but the maze is {width} wide")));
+        }
+
+        for (col_num, tile) in row.chars().enumerate() {
+            let pos = Vector2(col_num as i32, row_num as i32);
+            match tile {
+                _ if maze::TILES.contains(&tile) => {},
+                '0'..='9' => teleporters.entry(tile).or_default().push(pos),
+                _ => match Character::try_from(tile) {
+                    Ok(character) => spawns.entry(character).or_default().push(pos),
+                    Err(tile) => diagnostics.push(Diagnostic::error(Some(pos), format!("Unknown tile '{tile}'"))),
+                },
+            }
+        }
+    }
+
+    let mut teleporters: Vec<(char, Vec<Vector2>)> = teleporters.into_iter().collect();
+    teleporters.sort_by_key(|(digit, _)| *digit);
+    for (digit, ends) in teleporters {
+        if ends.len() != 2 {
+            diagnostics.push(Diagnostic::error(Some(ends[0]), format!("Teleporter {digit} needs exactly 2 ends but has {}", ends.len())));
+        }
+    }
+
+    for character in Character::characters() {
+        match spawns.get(&character).map(|found| found.as_slice()) {
+            None => diagnostics.push(Diagnostic::error(None, format!("{character} has no spawn"))),
+            Some([_]) => {},
+            Some(found) => diagnostics.push(Diagnostic::error(Some(found[1]), format!("{character} spawns more than once"))),
+        }
+    }
+
+    if has_errors(&diagnostics) { return diagnostics; }
+
+    match maze::parse_maze(text) {
+        Ok(grid) => diagnostics.extend(validate_grid(&grid)),
+        Err(err) => diagnostics.push(Diagnostic::error(None, err.to_string())),
+    }
+
+    diagnostics
+}
+
+/// Validates a grid. Errors are ragged rows, spawns outside the maze or in walls, teleporters that lead
+/// into walls, pellets Rucman can't reach and ghosts that can't reach Rucman, keeping in mind the tiles only
+/// some of them can cross. Warnings are scatter positions in walls and dead ends.
+pub fn validate_grid(grid: &Grid) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let maze = grid.get_maze();
+
+    for (row_num, row_width) in grid.get_ragged_rows() {
+        diagnostics.push(Diagnostic::error(Some(Vector2(0, *row_num as i32)), format!("Row is {row_width} wide but the maze is {} wide", grid.get_width())));
+    }
+    if has_errors(&diagnostics) { return diagnostics; } // The rest of the checks expect a rectangular maze.
+
+    for character in Character::characters() {
+        let spawn = grid.get_spawn(character);
+        if !grid.is_valid_pos(&spawn) {
+            diagnostics.push(Diagnostic::error(Some(spawn), format!("{character} spawns outside the maze or in a wall")));
+        }
+
+        if character == Character::Rucman { continue; }
+
+        let scatter_position = grid.get_scatter_position(character);
+        if !grid.is_valid_pos(&scatter_position) {
+            diagnostics.push(Diagnostic::warning(Some(scatter_position), format!("{character} scatters outside the maze or to a wall")));
+        }
+    }
+
+    for (row_num, row) in maze.iter().enumerate() {
+        for (col_num, point) in row.iter().enumerate() {
+            let pos = Vector2(col_num as i32, row_num as i32);
+            if let GridPoint::Teleporter(portal) = point && !grid.is_valid_pos(&portal.destination) {
+                let dest = portal.destination;
+                diagnostics.push(Diagnostic::error(Some(pos), format!("Teleporter leads to ({}, {}) which is outside the maze or in a wall", dest.0, dest.1)));
+            }
+        }
+    }
+
+    let rucman_spawn = grid.get_spawn(Character::Rucman);
+    if grid.is_valid_pos(&rucman_spawn) {
+        let reachable = flood_fill(grid, rucman_spawn, Mover::Rucman);
+        for (row_num, row) in maze.iter().enumerate() {
+            for (col_num, point) in row.iter().enumerate() {
+                let pos = Vector2(col_num as i32, row_num as i32);
+                if matches!(point, GridPoint::Pellet | GridPoint::PowerPellet) && !reachable.contains(&pos) {
+                    diagnostics.push(Diagnostic::error(Some(pos), String::from("Pellet can't be reached by Rucman")));
+                }
+            }
+        }
+
+        for character in Character::characters() {
+            let spawn = grid.get_spawn(character);
+            if grid.is_valid_pos(&spawn) && !flood_fill(grid, spawn, Mover::Ghost).contains(&rucman_spawn) {
+                diagnostics.push(Diagnostic::error(Some(spawn), format!("{character} can't reach Rucman")));
+            }
+        }
+    }
+
+    for (row_num, row) in maze.iter().enumerate() {
+        for col_num in 0..row.len() {
+            let pos = Vector2(col_num as i32, row_num as i32);
+            let is_teleporter = matches!(grid.get_point(&pos), Some(GridPoint::Teleporter(_))); // Stepping on one moves you on.
+            let exits = Direction::directions().into_iter().filter(|direction| grid.is_valid_pos(&pos.forward(*direction))).count();
+            if grid.is_valid_pos(&pos) && !is_teleporter && exits < 2 {
+                diagnostics.push(Diagnostic::warning(Some(pos), String::from("Dead end")));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Finds every position the mover can reach from the start, including through teleporters.
+pub fn flood_fill(grid: &Grid, start: Vector2, mover: Mover) -> HashSet<Vector2> {
+    let mut reached = HashSet::from([start]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(current) = frontier.pop_front() {
+        for next in neighbours(grid, current, mover) {
+            if reached.insert(next) {
+                frontier.push_back(next);
+            }
+        }
+    }
+
+    reached
+}
+
+/// Retrieves the positions the mover can get to in one step. Stepping onto a teleporter leads to its destination instead.
+fn neighbours(grid: &Grid, pos: Vector2, mover: Mover) -> Vec<Vector2> {
+    let mut res = Vec::new();
+    for direction in Direction::directions() {
+        if let Some((next, _)) = grid.try_move(&pos, direction, mover) && !res.contains(&next) {
+            res.push(next);
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::grid::Portal;
+
+    fn errors(diagnostics: &[Diagnostic]) -> Vec<&Diagnostic> {
+        diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).collect()
+    }
+
+    /// Tests if the bundled mazes have no errors.
+    #[test]
+    fn bundled_mazes_are_valid() {
+        assert!(!has_errors(&validate_grid(&Grid::new())));
+        assert!(!has_errors(&validate_maze_text(include_str!("../mazes/classic.maze"))));
+        assert!(!has_errors(&validate_maze_text(include_str!("../mazes/lattice.maze"))));
+    }
+
+    /// Tests if every text problem is reported rather than just the first.
+    #[test]
+    fn reports_all_text_problems() {
+        let diagnostics = validate_maze_text("#######\n#RBPx#\n#####y#\n1#######\n");
+        let errors = errors(&diagnostics);
+        assert!(errors.iter().any(|error| error.message == "Unknown tile 'x'" && error.position == Some(Vector2(4, 1))));
+        assert!(errors.iter().any(|error| error.message == "Unknown tile 'y'"));
+        assert!(errors.iter().any(|error| error.message.starts_with("Row is 6 wide")));
+        assert!(errors.iter().any(|error| error.message.starts_with("Teleporter 1 needs exactly 2 ends")));
+        assert!(errors.iter().any(|error| error.message == "Clyde has no spawn"));
+
+        let diagnostics = validate_maze_text("@spawn clyde 5 1\n@spawn inky 1 1\n#######\n#RBPI.#\n#######\n");
+        assert!(!diagnostics.iter().any(|error| error.message == "Clyde has no spawn"));
+        assert!(diagnostics.iter().any(|error| error.message == "Inky spawns more than once" && error.position == Some(Vector2(4, 1))));
+    }
+
+    /// Tests if pellets walled off from Rucman are unreachable.
+    #[test]
+    fn unreachable_pellets() {
+        let diagnostics = validate_maze_text("#########\n#RBPIC#.#\n#########\n");
+        let errors = errors(&diagnostics);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].position, Some(Vector2(7, 1)));
+    }
+
+    /// Tests if teleporters connect otherwise separate areas.
+    #[test]
+    fn teleporters_connect() {
+        let diagnostics = validate_maze_text("#########\n1RBPIC#.1\n#########\n");
+        assert!(!has_errors(&diagnostics));
+    }
+
+    /// Tests if dead ends are warnings.
+    #[test]
+    fn dead_ends() {
+        let diagnostics = validate_maze_text("########\n#RBPIC.#\n########\n");
+        assert!(!has_errors(&diagnostics));
+        assert!(diagnostics.contains(&Diagnostic::warning(Some(Vector2(1, 1)), String::from("Dead end"))));
+        assert!(diagnostics.contains(&Diagnostic::warning(Some(Vector2(6, 1)), String::from("Dead end"))));
+    }
+
+    /// Tests if doors and one-way tiles are taken into account when checking what can be reached.
+    #[test]
+    fn special_tiles_block() {
+        let diagnostics = validate_maze_text("##########\n#R.-.BPIC#\n##########\n");
+        assert!(errors(&diagnostics).iter().any(|error| error.message == "Pellet can't be reached by Rucman" && error.position == Some(Vector2(4, 1))));
+        assert!(!errors(&diagnostics).iter().any(|error| error.message.ends_with("can't reach Rucman")));
+
+        let diagnostics = validate_maze_text("##########\n#R.<.BPIC#\n##########\n");
+        assert!(errors(&diagnostics).iter().any(|error| error.message == "Pellet can't be reached by Rucman"));
+        assert!(!errors(&diagnostics).iter().any(|error| error.message.ends_with("can't reach Rucman")));
+
+        let diagnostics = validate_maze_text("##########\n#R.>.BPIC#\n##########\n");
+        assert_eq!(errors(&diagnostics).len(), 4);
+    }
+
+    /// Tests if grids built without a maze file are checked for ragged rows, bad spawns and teleporters.
+    #[test]
+    fn grid_problems() {
+        let ragged = Grid::from(vec![vec!['█', '█', '█'], vec!['█', '.']]);
+        assert!(errors(&validate_grid(&ragged))[0].message.starts_with("Row is 2 wide"));
+
+        let mut grid = Grid::new();
+        grid.set_spawn(Character::Pinky, Vector2(0, 0));
+        grid.add_portal(Vector2(26, 12), Portal { destination: Vector2(26, 11), exit: None, name: None }).unwrap();
+        let diagnostics = validate_grid(&grid);
+        let errors = errors(&diagnostics);
+        assert!(errors.iter().any(|error| error.message == "Pinky spawns outside the maze or in a wall"));
+        assert!(errors.iter().any(|error| error.position == Some(Vector2(26, 12))));
+    }
+
+    /// Tests if one-way teleporters only connect in one direction.
+    #[test]
+    fn one_way_teleporters() {
+        let text = "@portal in 7 1 -> 7 3\n#########\n#RBPIC. #\n#########\n#...    #\n#########\n";
+        assert!(!has_errors(&validate_maze_text(text)));
+
+        let trapped = text.replace("#RBPIC. #", "#R.PIC. #").replace("#...    #", "#...B   #");
+        let diagnostics = validate_maze_text(&trapped);
+        assert!(errors(&diagnostics).iter().any(|error| error.message == "Blinky can't reach Rucman"));
+    }
+}